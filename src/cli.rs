@@ -1,4 +1,13 @@
-use clap::{arg, Command};
+use std::{path::PathBuf, str::FromStr};
+
+use clap::{arg, value_parser, ArgAction, Command};
+
+use crate::{
+    error::Error,
+    output::OutputFormat,
+    todo::{ImportMode, Status},
+    transfer::Format,
+};
 
 pub fn cli() -> Command {
     Command::new("alle")
@@ -6,6 +15,31 @@ pub fn cli() -> Command {
         .about("Terminal TODO manager")
         .subcommand_required(true)
         .arg_required_else_help(true)
+        .arg(
+            arg!(db: --db <PATH> "The database to use, overrides ALLE_DB")
+                .value_parser(value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            arg!(list_name: -l --"list-name" <NAME> "Use the named list instead of the default one")
+                .conflicts_with("db")
+                .global(true),
+        )
+        .arg(
+            arg!(output: -o --output <FORMAT> "How to print todos: table, json, jsonl, csv or markdown")
+                .value_parser(parse_with::<OutputFormat>(OutputFormat::names()))
+                .default_value("table")
+                .global(true),
+        )
+        .arg(
+            arg!(global: -g --global "Ignore the project's .alle database")
+                .conflicts_with("db")
+                .global(true),
+        )
+        .subcommand(
+            Command::new("init")
+                .about("Create a TODO list for the project in the current directory"),
+        )
         .subcommand(
             Command::new("add")
                 .about("Add a TODO")
@@ -14,32 +48,87 @@ pub fn cli() -> Command {
                 .arg(arg!(note: -n <NOTE>))
                 .arg_required_else_help(false)
                 .arg(arg!(due: -d <DUE_DATE>))
+                .arg_required_else_help(false)
+                .arg(arg!(priority: -p --priority <PRIORITY>))
+                .arg_required_else_help(false)
+                .arg(arg!(repeat: -r --repeat <RULE> "e.g. weekly or every 3 days"))
                 .arg_required_else_help(false),
         )
         .subcommand(
             Command::new("list")
                 .about("List all TODOs")
-                .arg(arg!(status: -s <STATUS>))
+                .arg(
+                    arg!(status: -s <STATUS> "todo, in-progress, blocked, done or cancelled")
+                        .value_parser(parse_status),
+                )
+                .arg_required_else_help(false)
+                .arg(
+                    arg!(tag: -t <TAG> "Repeatable, prefix with ! to exclude")
+                        .action(ArgAction::Append),
+                )
+                .arg_required_else_help(false)
+                .arg(arg!(any_tag: --"any-tag" "Match any of the given tags instead of all"))
+                .arg_required_else_help(false)
+                .arg(arg!(priority: -p --priority <PRIORITY>))
+                .arg_required_else_help(false)
+                .arg(
+                    arg!(overdue: --overdue "Only TODOs past their due date")
+                        .conflicts_with_all(["due_today", "due_within"]),
+                )
+                .arg_required_else_help(false)
+                .arg(
+                    arg!(due_today: --"due-today" "Only TODOs due today")
+                        .conflicts_with("due_within"),
+                )
+                .arg_required_else_help(false)
+                .arg(arg!(due_within: --"due-within" <DURATION> "e.g. 3d, 2w or 12h"))
+                .arg_required_else_help(false)
+                .arg(arg!(query: -q --query <QUERY> "e.g. \"status:todo tag:work due<+1w\""))
                 .arg_required_else_help(false)
-                .arg(arg!(tag: -t <TAG>))
+                .arg(arg!(sort: --sort <KEYS> "e.g. priority:desc,due"))
+                .arg_required_else_help(false)
+                .arg(arg!(group_by_tag: --"group-by-tag" "Group a Markdown checklist by tag"))
+                .arg_required_else_help(false),
+        )
+        .subcommand(
+            Command::new("search")
+                .about("Search the names, notes and tags of TODOs")
+                .arg(arg!(<PATTERN> "The text to look for"))
+                .arg_required_else_help(true)
+                .arg(arg!(regex: -r --regex "Treat the pattern as a regular expression"))
+                .arg_required_else_help(false)
+                .arg(
+                    arg!(fuzzy: -f --fuzzy "Match the pattern's letters in order")
+                        .conflicts_with("regex"),
+                )
                 .arg_required_else_help(false),
         )
         .subcommand(
             Command::new("complete")
                 .about("Complete a TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
                 .arg_required_else_help(true),
         )
         .subcommand(
             Command::new("uncomplete")
                 .about("Uncomplete a TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("set-status")
+                .about("Set the status of a given TODO")
+                .arg(arg!(<TODO> "The ID or name of the todo"))
+                .arg_required_else_help(true)
+                .arg(
+                    arg!(<STATUS> "The new status").value_parser(parse_status),
+                )
                 .arg_required_else_help(true),
         )
         .subcommand(
             Command::new("add-note")
                 .about("Add a note for a given TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
                 .arg_required_else_help(true)
                 .arg(arg!(<NOTE> "The note to add"))
                 .arg_required_else_help(true),
@@ -47,19 +136,21 @@ pub fn cli() -> Command {
         .subcommand(
             Command::new("edit-note")
                 .about("Edit the note for a given TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
+                .arg_required_else_help(true)
+                .arg(arg!(<NOTE> "The new note"))
                 .arg_required_else_help(true),
         )
         .subcommand(
             Command::new("remove-note")
                 .about("Remove the note for a given TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
                 .arg_required_else_help(true),
         )
         .subcommand(
             Command::new("add-tag")
                 .about("Add a tag to a given TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
                 .arg_required_else_help(true)
                 .arg(arg!(<TAG> "The tag to add"))
                 .arg_required_else_help(true),
@@ -67,7 +158,7 @@ pub fn cli() -> Command {
         .subcommand(
             Command::new("remove-tag")
                 .about("Remove a tag from a given TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
                 .arg_required_else_help(true)
                 .arg(arg!(<TAG> "The tag to remove"))
                 .arg_required_else_help(true),
@@ -75,7 +166,7 @@ pub fn cli() -> Command {
         .subcommand(
             Command::new("add-due-date")
                 .about("Add a due date to a given TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
                 .arg_required_else_help(true)
                 .arg(arg!(<DATE> "The date to add"))
                 .arg_required_else_help(true),
@@ -83,7 +174,7 @@ pub fn cli() -> Command {
         .subcommand(
             Command::new("change-due-date")
                 .about("Change the due date for a given TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
                 .arg_required_else_help(true)
                 .arg(arg!(<DATE> "The date to add"))
                 .arg_required_else_help(true),
@@ -91,14 +182,101 @@ pub fn cli() -> Command {
         .subcommand(
             Command::new("remove-due-date")
                 .about("Remove the due date from a given TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("set-priority")
+                .about("Set the priority of a given TODO")
+                .arg(arg!(<TODO> "The ID or name of the todo"))
+                .arg_required_else_help(true)
+                .arg(arg!(<PRIORITY> "low, medium, high, critical or none"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("set-recurrence")
+                .about("Make a given TODO repeat")
+                .arg(arg!(<TODO> "The ID or name of the todo"))
+                .arg_required_else_help(true)
+                .arg(arg!(<RULE> "e.g. daily, weekly, every 3 days, FREQ=MONTHLY or none"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("history")
+                .about("Show the completed occurrences of a recurring TODO")
+                .arg(arg!(<TODO> "The ID or name of the todo"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("rename")
+                .about("Rename a TODO")
+                .arg(arg!(<TODO> "The ID or name of the todo"))
+                .arg_required_else_help(true)
+                .arg(arg!(<NEW_NAME> "The new name of the todo"))
                 .arg_required_else_help(true),
         )
         .subcommand(
             Command::new("delete")
                 .about("Delete a TODO")
-                .arg(arg!(<NAME> "The name of the todo"))
+                .arg(arg!(<TODO> "The ID or name of the todo"))
                 .arg_required_else_help(true),
         )
+        .subcommand(
+            Command::new("export")
+                .about("Export all TODOs")
+                .arg(
+                    arg!([FILE] "The file to write, or standard output if not given")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(format: -f --format <FORMAT> "json, jsonl, csv, todotxt, ical or markdown; defaults to the file extension, or json")
+                        .value_parser(parse_with::<Format>(Format::names())),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Import TODOs from an export")
+                .arg(
+                    arg!(<FILE> "The file to read, or - for standard input")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg_required_else_help(true)
+                .arg(
+                    arg!(format: -f --format <FORMAT> "json, jsonl, csv, todotxt, ical or markdown; defaults to the file extension, or json")
+                        .value_parser(parse_with::<Format>(Format::names())),
+                )
+                .arg(
+                    arg!(mode: -m --mode <MODE> "What to do with TODOs that already exist: merge, replace or skip-existing")
+                        .value_parser(parse_with::<ImportMode>(ImportMode::names()))
+                        .default_value("merge"),
+                ),
+        )
+        .subcommand(
+            Command::new("migrate").about("Upgrades stored TODOs to the current schema version"),
+        )
         .subcommand(Command::new("drop-db").about("Drops the database of TODOs"))
 }
+
+// Parses a value with its `FromStr`, so every spelling that accepts works,
+// and lists the canonical `names` when it fails.
+fn parse_with<T: FromStr<Err = Error>>(
+    names: &'static [&'static str],
+) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync + 'static {
+    move |value| {
+        value
+            .parse()
+            .map_err(|e: Error| format!("{} (expected one of {})", e, names.join(", ")))
+    }
+}
+
+// Accepts every spelling `Status::from_str` does, such as `InProgress` and
+// `in_progress`, and passes on the canonical name.
+fn parse_status(value: &str) -> Result<String, String> {
+    match Status::from_str(value) {
+        Ok(status) => Ok(status.names()[0].to_string()),
+        Err(e) => {
+            let names: Vec<&str> = Status::ALL.iter().map(|status| status.names()[0]).collect();
+            Err(format!("{} (expected one of {})", e, names.join(", ")))
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use std::{collections::BTreeMap, env, fmt::Write, fs, io, path::PathBuf};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    NaiveDate,
+};
+use comfy_table::Color;
+use serde::Deserialize;
+
+use crate::{
+    date,
+    error::Error,
+    todo::{Priority, Status},
+};
+
+/// The environment variable that points `alle` at a different config file.
+pub const CONFIG_ENV: &str = "ALLE_CONFIG";
+
+/// Settings read from `~/.config/alle/config.toml`. Everything is optional and
+/// falls back to the built-in behaviour.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The database used when no other one is picked; `~` is expanded.
+    pub database: Option<PathBuf>,
+    pub dates: Dates,
+    pub list: ListDefaults,
+    pub table: TableConfig,
+    pub colors: Colors,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Dates {
+    /// An extra strftime format accepted for due dates, such as `%m/%d/%Y`.
+    pub input_format: Option<String>,
+    /// The strftime format due dates are shown in.
+    pub output_format: String,
+}
+
+impl Default for Dates {
+    fn default() -> Self {
+        Dates {
+            input_format: None,
+            output_format: String::from(date::LEGACY_FORMAT),
+        }
+    }
+}
+
+impl Dates {
+    /// Rewrites a date given in the configured input format as an ISO date,
+    /// leaving anything else to the usual due date parsing.
+    pub fn normalize(&self, input: &str) -> String {
+        self.input_format
+            .as_ref()
+            .and_then(|format| NaiveDate::parse_from_str(input.trim(), format).ok())
+            .map_or_else(|| input.to_string(), |date| date.to_string())
+    }
+}
+
+/// What `list` shows when it is given no filters or sort of its own.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListDefaults {
+    pub query: Option<String>,
+    pub sort: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableConfig {
+    pub style: TableStyle,
+    pub columns: Vec<TableColumn>,
+}
+
+impl Default for TableConfig {
+    fn default() -> Self {
+        TableConfig {
+            style: TableStyle::default(),
+            columns: TableColumn::ALL.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    #[default]
+    Rounded,
+    Full,
+    Condensed,
+    Ascii,
+    Markdown,
+    Plain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableColumn {
+    Id,
+    Name,
+    Status,
+    Priority,
+    Due,
+    Repeat,
+    Note,
+    Tags,
+}
+
+impl TableColumn {
+    pub const ALL: [TableColumn; 8] = [
+        TableColumn::Id,
+        TableColumn::Name,
+        TableColumn::Status,
+        TableColumn::Priority,
+        TableColumn::Due,
+        TableColumn::Repeat,
+        TableColumn::Note,
+        TableColumn::Tags,
+    ];
+
+    pub fn header(self) -> &'static str {
+        match self {
+            TableColumn::Id => "ID",
+            TableColumn::Name => "Name",
+            TableColumn::Status => "Status",
+            TableColumn::Priority => "Priority",
+            TableColumn::Due => "Due Date",
+            TableColumn::Repeat => "Repeat",
+            TableColumn::Note => "Note",
+            TableColumn::Tags => "Tags",
+        }
+    }
+}
+
+/// The colors statuses, priorities and due dates are shown in. `None` leaves
+/// the text uncolored.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "BTreeMap<String, String>")]
+pub struct Colors {
+    pub todo: Option<Color>,
+    pub in_progress: Option<Color>,
+    pub blocked: Option<Color>,
+    pub done: Option<Color>,
+    pub cancelled: Option<Color>,
+    pub critical: Option<Color>,
+    pub high: Option<Color>,
+    pub medium: Option<Color>,
+    pub low: Option<Color>,
+    pub overdue: Option<Color>,
+    pub due_soon: Option<Color>,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            todo: Some(Color::Red),
+            in_progress: Some(Color::Yellow),
+            blocked: Some(Color::Magenta),
+            done: Some(Color::Green),
+            cancelled: Some(Color::DarkGrey),
+            critical: Some(Color::Red),
+            high: Some(Color::Yellow),
+            medium: Some(Color::Blue),
+            low: Some(Color::DarkGrey),
+            overdue: Some(Color::Red),
+            due_soon: Some(Color::Yellow),
+        }
+    }
+}
+
+impl Colors {
+    pub fn status(&self, status: Status) -> Option<Color> {
+        match status {
+            Status::ToDo => self.todo,
+            Status::InProgress => self.in_progress,
+            Status::Blocked => self.blocked,
+            Status::Done => self.done,
+            Status::Cancelled => self.cancelled,
+        }
+    }
+
+    pub fn priority(&self, priority: Priority) -> Option<Color> {
+        match priority {
+            Priority::Critical => self.critical,
+            Priority::High => self.high,
+            Priority::Medium => self.medium,
+            Priority::Low => self.low,
+        }
+    }
+}
+
+impl TryFrom<BTreeMap<String, String>> for Colors {
+    type Error = String;
+
+    fn try_from(settings: BTreeMap<String, String>) -> Result<Self, Self::Error> {
+        let mut colors = Colors::default();
+        for (key, value) in settings {
+            let color = parse_color(&value)?;
+            let slot = match key.as_str() {
+                "todo" => &mut colors.todo,
+                "in_progress" => &mut colors.in_progress,
+                "blocked" => &mut colors.blocked,
+                "done" => &mut colors.done,
+                "cancelled" => &mut colors.cancelled,
+                "critical" => &mut colors.critical,
+                "high" => &mut colors.high,
+                "medium" => &mut colors.medium,
+                "low" => &mut colors.low,
+                "overdue" => &mut colors.overdue,
+                "due_soon" => &mut colors.due_soon,
+                _ => return Err(format!("unknown color setting `{}`", key)),
+            };
+            *slot = color;
+        }
+
+        Ok(colors)
+    }
+}
+
+// Accepts the terminal color names, `none` and `#rrggbb`.
+fn parse_color(value: &str) -> Result<Option<Color>, String> {
+    let color = match value.to_lowercase().replace(['-', ' '], "_").as_str() {
+        "none" => return Ok(None),
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "dark_red" => Color::DarkRed,
+        "green" => Color::Green,
+        "dark_green" => Color::DarkGreen,
+        "yellow" => Color::Yellow,
+        "dark_yellow" => Color::DarkYellow,
+        "blue" => Color::Blue,
+        "dark_blue" => Color::DarkBlue,
+        "magenta" => Color::Magenta,
+        "dark_magenta" => Color::DarkMagenta,
+        "cyan" => Color::Cyan,
+        "dark_cyan" => Color::DarkCyan,
+        "white" => Color::White,
+        "grey" | "gray" => Color::Grey,
+        "dark_grey" | "dark_gray" => Color::DarkGrey,
+        hex => parse_hex(hex).ok_or_else(|| format!("{} is not a valid color", value))?,
+    };
+
+    Ok(Some(color))
+}
+
+fn parse_hex(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+    Some(Color::Rgb {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+/// Where the config file is read from: `ALLE_CONFIG`, or `config.toml` in the
+/// platform's config directory.
+pub fn config_path() -> Option<PathBuf> {
+    match env::var_os(CONFIG_ENV) {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => dirs::config_dir().map(|dir| dir.join("alle").join("config.toml")),
+    }
+}
+
+/// Loads the config file, or the defaults if there is none.
+pub fn load() -> Result<Config, Error> {
+    let path = match config_path() {
+        Some(path) => path,
+        None => return Ok(Config::default()),
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(contents) => parse_config(&contents).map_err(|e| match e {
+            Error::InvalidConfig(e) => Error::InvalidConfig(format!("{}: {}", path.display(), e)),
+            e => e,
+        }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(e) => Err(Error::Io(e)),
+    }
+}
+
+/// Parses the contents of a config file.
+pub fn parse_config(contents: &str) -> Result<Config, Error> {
+    let mut config: Config =
+        toml::from_str(contents).map_err(|e| Error::InvalidConfig(e.to_string()))?;
+
+    for format in config
+        .dates
+        .input_format
+        .iter()
+        .chain([&config.dates.output_format])
+    {
+        if StrftimeItems::new(format).any(|item| item == Item::Error) {
+            return Err(Error::InvalidConfig(format!(
+                "{} is not a valid date format",
+                format
+            )));
+        }
+    }
+    // Specifiers such as `%H` parse but can't be shown for a date alone.
+    let output_format = &config.dates.output_format;
+    let mut formatted = String::new();
+    if write!(formatted, "{}", NaiveDate::MIN.format(output_format)).is_err() {
+        return Err(Error::InvalidConfig(format!(
+            "{} can't format a date on its own",
+            output_format
+        )));
+    }
+
+    if let Some(database) = &config.database {
+        if let (Ok(rest), Some(home)) = (database.strip_prefix("~"), dirs::home_dir()) {
+            config.database = Some(home.join(rest));
+        }
+    }
+
+    Ok(config)
+}
@@ -0,0 +1,374 @@
+use std::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveTime, Offset,
+    TimeZone, Weekday,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::Error;
+
+/// The format due dates were stored and entered in before they became dates.
+pub const LEGACY_FORMAT: &str = "%d-%m-%Y";
+
+/// When a todo is due: either a whole day or a moment with a UTC offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Due {
+    Date(NaiveDate),
+    DateTime(DateTime<FixedOffset>),
+}
+
+impl Due {
+    /// The moment the todo becomes overdue. A date without a time is due
+    /// until the end of that day in the local time zone.
+    pub fn deadline(&self) -> DateTime<Local> {
+        match self {
+            Due::Date(date) => {
+                let end_of_day = date.and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+                Local
+                    .from_local_datetime(&end_of_day)
+                    .latest()
+                    .unwrap_or_else(|| Local.from_utc_datetime(&end_of_day))
+            }
+            Due::DateTime(datetime) => datetime.with_timezone(&Local),
+        }
+    }
+
+    /// The local calendar day the todo is due on.
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            Due::Date(date) => *date,
+            Due::DateTime(datetime) => datetime.with_timezone(&Local).date_naive(),
+        }
+    }
+
+    pub fn is_overdue(&self, now: DateTime<Local>) -> bool {
+        self.deadline() < now
+    }
+
+    /// Whether the todo is not yet overdue but will be by `now + within`.
+    /// Dates without a time count as due soon for the whole day.
+    pub fn is_due_within(&self, now: DateTime<Local>, within: Duration) -> bool {
+        let until = now + within;
+        let soon = match self {
+            Due::Date(date) => *date <= until.date_naive(),
+            Due::DateTime(_) => self.deadline() <= until,
+        };
+
+        soon && !self.is_overdue(now)
+    }
+
+    /// Formats the due date with `date_format`, adding the local time of day
+    /// when there is one. Formats that can't show a date, such as ones asking
+    /// for a time, fall back to [`LEGACY_FORMAT`].
+    pub fn format(&self, date_format: &str) -> String {
+        self.try_format(date_format)
+            .or_else(|_| self.try_format(LEGACY_FORMAT))
+            .unwrap_or_default()
+    }
+
+    fn try_format(&self, date_format: &str) -> Result<String, fmt::Error> {
+        let mut formatted = String::new();
+        match self {
+            Due::Date(date) => write!(formatted, "{}", date.format(date_format))?,
+            Due::DateTime(datetime) => write!(
+                formatted,
+                "{}",
+                datetime
+                    .with_timezone(&Local)
+                    .format(&format!("{} %H:%M", date_format))
+            )?,
+        }
+
+        Ok(formatted)
+    }
+}
+
+impl fmt::Display for Due {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Due::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Due::DateTime(datetime) => write!(f, "{}", datetime.to_rfc3339()),
+        }
+    }
+}
+
+/// Parses the canonical forms written by [`Due`]'s `Display` implementation,
+/// as well as legacy `%d-%m-%Y` dates.
+impl FromStr for Due {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(Due::Date(date));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(s, LEGACY_FORMAT) {
+            return Ok(Due::Date(date));
+        }
+
+        DateTime::parse_from_rfc3339(s)
+            .map(Due::DateTime)
+            .map_err(|_| Error::InvalidDate)
+    }
+}
+
+impl Serialize for Due {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Due {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parses a due date with an optional time of day relative to `now`.
+///
+/// Accepts everything [`parse_date`] does, optionally followed by a time
+/// (`14:30`, `9am`, `6:15pm`) and a time zone (`UTC`, `+02:00`). A time on its
+/// own means today, and times without a zone are in the local time zone. Full
+/// RFC 3339 timestamps and offsets in hours (`+3h`) are accepted as well.
+pub fn parse_due(input: &str, now: DateTime<Local>) -> Result<Due, Error> {
+    let input = input.trim();
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(input) {
+        return Ok(Due::DateTime(datetime));
+    }
+    if let Some(hours) = input
+        .strip_prefix('+')
+        .and_then(|offset| offset.strip_suffix('h'))
+        .and_then(|hours| hours.parse::<i64>().ok())
+    {
+        let datetime = now
+            .checked_add_signed(Duration::hours(hours))
+            .ok_or(Error::InvalidDate)?;
+        return Ok(Due::DateTime(
+            datetime.with_timezone(&datetime.offset().fix()),
+        ));
+    }
+
+    // `2026-11-01T09:00` reads the same as `2026-11-01 09:00`.
+    let input = match input.split_once('T') {
+        Some((date, time)) if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() => {
+            format!("{} {}", date, time)
+        }
+        _ => input.to_string(),
+    };
+
+    let mut tokens: Vec<&str> = input.split_whitespace().collect();
+    let mut offset = None;
+    if tokens.len() >= 2 && parse_time(tokens[tokens.len() - 2]).is_some() {
+        offset = parse_offset_zone(tokens[tokens.len() - 1]);
+        if offset.is_some() {
+            tokens.pop();
+        }
+    }
+    let time = tokens.last().and_then(|token| parse_time(token));
+    if time.is_some() {
+        tokens.pop();
+    }
+
+    let date = match tokens.is_empty() {
+        true => now.date_naive(),
+        false => parse_date(&tokens.join(" "), now.date_naive())?,
+    };
+
+    let time = match time {
+        Some(time) => time,
+        None => return Ok(Due::Date(date)),
+    };
+    let naive = date.and_time(time);
+    let datetime = match offset {
+        Some(offset) => offset.from_local_datetime(&naive).single(),
+        None => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|datetime| datetime.with_timezone(&datetime.offset().fix())),
+    };
+
+    datetime.map(Due::DateTime).ok_or(Error::InvalidDate)
+}
+
+fn parse_time(input: &str) -> Option<NaiveTime> {
+    let input = input.to_lowercase();
+    for format in ["%H:%M", "%H:%M:%S"] {
+        if let Ok(time) = NaiveTime::parse_from_str(&input, format) {
+            return Some(time);
+        }
+    }
+
+    let (clock, afternoon) = match (input.strip_suffix("am"), input.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, false),
+        (_, Some(clock)) => (clock, true),
+        _ => return None,
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    if !(1..=12).contains(&hour) {
+        return None;
+    }
+
+    NaiveTime::from_hms_opt(hour % 12 + if afternoon { 12 } else { 0 }, minute, 0)
+}
+
+fn parse_offset_zone(input: &str) -> Option<FixedOffset> {
+    match input.to_lowercase().as_str() {
+        "z" | "utc" | "gmt" => return FixedOffset::east_opt(0),
+        _ => {}
+    }
+
+    let sign = match input.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits: String = input[1..].chars().filter(|c| *c != ':').collect();
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Selects todos by when they are due, relative to the current time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueFilter {
+    Overdue,
+    Today,
+    Within(Duration),
+}
+
+impl DueFilter {
+    pub fn matches(&self, due: &Due, now: DateTime<Local>) -> bool {
+        match self {
+            DueFilter::Overdue => due.is_overdue(now),
+            DueFilter::Today => due.date() == now.date_naive(),
+            DueFilter::Within(within) => due.is_due_within(now, *within),
+        }
+    }
+}
+
+/// Parses a span of time such as `3d`, `2w` or `12h`.
+pub fn parse_duration(input: &str) -> Result<Duration, Error> {
+    let input = input.trim().to_lowercase();
+    let invalid = || Error::InvalidDuration(input.clone());
+
+    let unit = input.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = input[..input.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    if amount < 0 {
+        return Err(invalid());
+    }
+
+    match unit {
+        'm' => Duration::try_minutes(amount),
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+/// Parses a due date relative to `today`.
+///
+/// Accepts ISO 8601 (`2026-11-01`), the legacy `01-11-2026` format, `today`,
+/// `tomorrow`, `yesterday`, weekday names (`friday`, `next fri`), `next week`,
+/// `next month`, `next year` and offsets such as `+3d`, `+2w`, `+1m` or `+1y`.
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, Error> {
+    let input = input.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&input, LEGACY_FORMAT) {
+        return Ok(date);
+    }
+
+    let date = match input.as_str() {
+        "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        "yesterday" => today.pred_opt(),
+        "next week" => today.checked_add_signed(Duration::weeks(1)),
+        "next month" => today.checked_add_months(Months::new(1)),
+        "next year" => today.checked_add_months(Months::new(12)),
+        _ => {
+            if let Some(offset) = input.strip_prefix('+') {
+                parse_offset(offset, today)
+            } else if let Some(day) = input.strip_prefix("next ") {
+                parse_weekday(day).map(|weekday| next_weekday(today, weekday, false))
+            } else {
+                parse_weekday(&input).map(|weekday| next_weekday(today, weekday, true))
+            }
+        }
+    };
+
+    date.ok_or(Error::InvalidDate)
+}
+
+fn parse_offset(offset: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let unit = offset.chars().last()?;
+    let amount: u32 = offset[..offset.len() - unit.len_utf8()].parse().ok()?;
+
+    match unit {
+        'd' => today.checked_add_signed(Duration::days(amount.into())),
+        'w' => today.checked_add_signed(Duration::weeks(amount.into())),
+        'm' => today.checked_add_months(Months::new(amount)),
+        'y' => today.checked_add_months(Months::new(amount.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+fn parse_weekday(input: &str) -> Option<Weekday> {
+    let weekday = match input.trim() {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+
+    Some(weekday)
+}
+
+// The first `weekday` after `today`, or `today` itself when `include_today` is
+// set and it falls on that weekday.
+fn next_weekday(today: NaiveDate, weekday: Weekday, include_today: bool) -> NaiveDate {
+    let mut days =
+        (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    if days == 0 && !include_today {
+        days = 7;
+    }
+
+    today + Duration::days(days.into())
+}
+
+/// Reads a stored due date, also accepting the empty string and `%d-%m-%Y`
+/// values written by older versions.
+pub(crate) fn deserialize_due_date<'de, D>(deserializer: D) -> Result<Option<Due>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    match value.as_deref() {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
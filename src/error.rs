@@ -0,0 +1,103 @@
+use std::{error, fmt, io};
+
+use crate::store::StoreError;
+
+#[derive(Debug)]
+pub enum Error {
+    NotFound,
+    AlreadyExists,
+    EmptyName,
+    NoteAlreadyExists,
+    TagAlreadyExists,
+    TagNotFound,
+    NotCompleted,
+    InvalidDate,
+    InvalidStatus(String),
+    InvalidPriority(String),
+    InvalidSort(String),
+    InvalidDuration(String),
+    InvalidRecurrence(String),
+    InvalidQuery(String),
+    InvalidPattern(String),
+    InvalidListName(String),
+    NoDataDir,
+    InvalidConfig(String),
+    InvalidFormat(String),
+    InvalidImportMode(String),
+    InvalidImport(String),
+    InvalidExport(String),
+    Storage(StoreError),
+    Conflict,
+    Corrupt(String),
+    UnsupportedVersion(u32),
+    Encode(serde_json::Error),
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound => write!(f, "Todo with this ID or name does not exist"),
+            Error::AlreadyExists => write!(f, "Todo with this name already exists"),
+            Error::EmptyName => write!(f, "A todo needs a name"),
+            Error::NoteAlreadyExists => write!(f, "This todo already has a note"),
+            Error::TagAlreadyExists => {
+                write!(f, "This tag is has already been added to this todo")
+            }
+            Error::TagNotFound => write!(f, "This tag does not exist for this todo"),
+            Error::NotCompleted => write!(f, "This todo has no completion to undo"),
+            Error::InvalidDate => write!(f, "Invalid date format"),
+            Error::InvalidStatus(s) => write!(f, "{} is not a valid status", s),
+            Error::InvalidPriority(s) => write!(f, "{} is not a valid priority", s),
+            Error::InvalidSort(s) => write!(f, "{} is not a valid sort key", s),
+            Error::InvalidDuration(s) => write!(f, "{} is not a valid duration", s),
+            Error::InvalidRecurrence(s) => write!(f, "{} is not a valid recurrence", s),
+            Error::InvalidQuery(s) => write!(f, "{} is not a valid query", s),
+            Error::InvalidPattern(s) => write!(f, "{} is not a valid search pattern", s),
+            Error::InvalidListName(s) => write!(f, "{} is not a valid list name", s),
+            Error::NoDataDir => write!(
+                f,
+                "Could not find a data directory, pass --db or set ALLE_DB"
+            ),
+            Error::InvalidConfig(e) => write!(f, "Invalid config file: {}", e),
+            Error::InvalidFormat(s) => write!(f, "{} is not a valid format", s),
+            Error::InvalidImportMode(s) => write!(f, "{} is not a valid import mode", s),
+            Error::InvalidImport(e) => write!(f, "Could not import todos: {}", e),
+            Error::InvalidExport(e) => write!(f, "Could not export todos: {}", e),
+            Error::Storage(e) => write!(f, "Storage error: {}", e),
+            Error::Conflict => write!(
+                f,
+                "Todos were changed by another process at the same time, try again"
+            ),
+            Error::Corrupt(key) => write!(f, "Stored todo {} is corrupt", key),
+            Error::UnsupportedVersion(v) => {
+                write!(f, "Stored todo has unsupported schema version {}", v)
+            }
+            Error::Encode(e) => write!(f, "Could not encode todo: {}", e),
+            Error::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Storage(e) => Some(e),
+            Error::Encode(e) => Some(e),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<StoreError> for Error {
+    fn from(e: StoreError) -> Self {
+        Error::Storage(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,548 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+
+use crate::{
+    date::Due,
+    error::Error,
+    recurrence::{Frequency, Recurrence},
+    todo::{Priority, Status, Todo},
+};
+
+const DATE_FORMAT: &str = "%Y%m%d";
+const UTC_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const LOCAL_FORMAT: &str = "%Y%m%dT%H%M%S";
+// Content lines longer than this many bytes are folded.
+const LINE_LIMIT: usize = 75;
+// iCalendar has no blocked status, so blocked todos are exported as needing
+// action with this property saying what they really are.
+const STATUS_PROPERTY: &str = "X-ALLE-STATUS";
+
+/// Writes `todos` as an iCalendar file with one VTODO each, stamped with
+/// `now`.
+pub fn write_calendar<W: Write>(
+    mut writer: W,
+    todos: &[Todo],
+    now: DateTime<Utc>,
+) -> Result<(), Error> {
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//alle//alle//EN"),
+    ];
+    for todo in todos {
+        lines.extend(vtodo(todo, now));
+    }
+    lines.push(String::from("END:VCALENDAR"));
+
+    for line in lines {
+        write!(writer, "{}\r\n", fold(&line))?;
+    }
+
+    Ok(())
+}
+
+fn vtodo(todo: &Todo, now: DateTime<Utc>) -> Vec<String> {
+    let mut lines = vec![
+        String::from("BEGIN:VTODO"),
+        format!("UID:{}-{:016x}@alle", todo.id, name_hash(&todo.name)),
+        format!("DTSTAMP:{}", now.format(UTC_FORMAT)),
+    ];
+
+    if let Some(created_at) = todo.created_at {
+        lines.push(format!("CREATED:{}", created_at.format(UTC_FORMAT)));
+    }
+    lines.push(format!("SUMMARY:{}", escape(&todo.name)));
+    if !todo.note.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape(&todo.note)));
+    }
+    match &todo.due_date {
+        Some(Due::Date(date)) => lines.push(format!("DUE;VALUE=DATE:{}", date.format(DATE_FORMAT))),
+        Some(Due::DateTime(datetime)) => lines.push(format!(
+            "DUE:{}",
+            datetime.with_timezone(&Utc).format(UTC_FORMAT)
+        )),
+        None => {}
+    }
+    lines.push(format!("STATUS:{}", status_name(todo.status)));
+    if todo.status == Status::Blocked {
+        lines.push(format!("{}:{}", STATUS_PROPERTY, todo.status.names()[0]));
+    }
+    if let Some(priority) = todo.priority {
+        lines.push(format!("PRIORITY:{}", priority_number(priority)));
+    }
+    if !todo.tags.is_empty() {
+        let tags: Vec<String> = todo.tags.iter().map(|tag| escape(tag)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    if let Some(recurrence) = todo.recurrence {
+        lines.push(format!("RRULE:{}", rrule(recurrence)));
+    }
+    lines.push(String::from("END:VTODO"));
+
+    lines
+}
+
+/// Reads the VTODOs in an iCalendar file, ignoring everything else in it.
+/// Repeat rules alle can't follow are dropped. Times in a named time zone are
+/// converted with the file's VTIMEZONE for it, and without one only the date
+/// of times in zones other than UTC and the local one is kept.
+pub fn read_calendar<R: Read>(mut reader: R) -> Result<Vec<Todo>, Error> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+    let lines = unfold(&contents);
+    let zones = time_zones(&lines);
+
+    let mut todos = Vec::new();
+    // The components the current line is nested in; only properties directly
+    // inside a VTODO count, not those of its alarms.
+    let mut components: Vec<String> = Vec::new();
+    let mut todo = Todo::default();
+    let mut has_summary = false;
+    let mut alle_status = None;
+
+    for (number, line) in lines {
+        let invalid = |e: &str| Error::InvalidImport(format!("line {}: {}", number, e));
+        let (name, params, value) =
+            split_line(&line).ok_or_else(|| invalid("not a content line"))?;
+
+        match name.as_str() {
+            "BEGIN" => {
+                if value.eq_ignore_ascii_case("VTODO") {
+                    todo = Todo::default();
+                    has_summary = false;
+                    alle_status = None;
+                }
+                components.push(value.to_uppercase());
+                continue;
+            }
+            "END" => {
+                if components.pop().as_deref() != Some(value.to_uppercase().as_str()) {
+                    return Err(invalid("unmatched END"));
+                }
+                if value.eq_ignore_ascii_case("VTODO") {
+                    if !has_summary {
+                        return Err(invalid("the VTODO has no SUMMARY"));
+                    }
+                    todo.status = alle_status.unwrap_or(todo.status);
+                    todos.push(std::mem::take(&mut todo));
+                }
+                continue;
+            }
+            _ if components.last().map(String::as_str) != Some("VTODO") => continue,
+            _ => {}
+        }
+
+        let property = |e: Error| invalid(&format!("{}: {}", name, e));
+        match name.as_str() {
+            "SUMMARY" => {
+                todo.name = unescape(&value);
+                has_summary = true;
+            }
+            "DESCRIPTION" => todo.note = unescape(&value),
+            "DUE" => todo.due_date = Some(parse_due(&params, &value, &zones).map_err(property)?),
+            "CREATED" => {
+                let created_at = parse_due(&params, &value, &zones).map_err(property)?;
+                todo.created_at = Some(created_at.deadline().with_timezone(&Utc));
+            }
+            "STATUS" => todo.status = parse_status(&value).map_err(property)?,
+            STATUS_PROPERTY => alle_status = Some(value.parse().map_err(property)?),
+            "PRIORITY" => todo.priority = parse_priority(&value).map_err(property)?,
+            "CATEGORIES" => {
+                for tag in split_list(&value) {
+                    if !tag.is_empty() && !todo.tags.contains(&tag) {
+                        todo.tags.push(tag);
+                    }
+                }
+            }
+            "RRULE" => todo.recurrence = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    if let Some(component) = components.last() {
+        return Err(Error::InvalidImport(format!(
+            "{} is never ended",
+            component
+        )));
+    }
+
+    Ok(todos)
+}
+
+// FNV-1a, which unlike the standard library's hasher is sure to give the same
+// hash in every build, so a todo keeps its UID from one export to the next.
+// Names are unique, so todos without an ID still get UIDs of their own.
+fn name_hash(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn status_name(status: Status) -> &'static str {
+    match status {
+        Status::ToDo | Status::Blocked => "NEEDS-ACTION",
+        Status::InProgress => "IN-PROCESS",
+        Status::Done => "COMPLETED",
+        Status::Cancelled => "CANCELLED",
+    }
+}
+
+fn parse_status(value: &str) -> Result<Status, Error> {
+    match value.to_uppercase().as_str() {
+        "NEEDS-ACTION" => Ok(Status::ToDo),
+        "IN-PROCESS" => Ok(Status::InProgress),
+        "COMPLETED" => Ok(Status::Done),
+        "CANCELLED" => Ok(Status::Cancelled),
+        _ => Err(Error::InvalidStatus(value.to_string())),
+    }
+}
+
+// iCalendar priorities run from 1 (highest) to 9, with 0 for none.
+fn priority_number(priority: Priority) -> u8 {
+    match priority {
+        Priority::Critical => 1,
+        Priority::High => 3,
+        Priority::Medium => 5,
+        Priority::Low => 9,
+    }
+}
+
+fn parse_priority(value: &str) -> Result<Option<Priority>, Error> {
+    match value.trim().parse::<u8>() {
+        Ok(0) => Ok(None),
+        Ok(1..=2) => Ok(Some(Priority::Critical)),
+        Ok(3..=4) => Ok(Some(Priority::High)),
+        Ok(5) => Ok(Some(Priority::Medium)),
+        Ok(6..=9) => Ok(Some(Priority::Low)),
+        _ => Err(Error::InvalidPriority(value.to_string())),
+    }
+}
+
+fn rrule(recurrence: Recurrence) -> String {
+    let frequency = match recurrence.frequency {
+        Frequency::Daily => "DAILY",
+        Frequency::Weekly => "WEEKLY",
+        Frequency::Monthly => "MONTHLY",
+        Frequency::Yearly => "YEARLY",
+    };
+
+    match recurrence.interval {
+        1 => format!("FREQ={}", frequency),
+        interval => format!("FREQ={};INTERVAL={}", frequency, interval),
+    }
+}
+
+// Dates are all-day; times ending in `Z` are UTC and others are in their TZID,
+// or else local. A TZID is looked up in `zones`, as there's no time zone
+// database, and a time in a zone that can't be found is cut to its date.
+fn parse_due(params: &[(String, String)], value: &str, zones: &Zones) -> Result<Due, Error> {
+    let is_date = params
+        .iter()
+        .any(|(name, value)| name == "VALUE" && value.eq_ignore_ascii_case("DATE"));
+    if is_date || !value.contains('T') {
+        return NaiveDate::parse_from_str(value, DATE_FORMAT)
+            .map(Due::Date)
+            .map_err(|_| Error::InvalidDate);
+    }
+
+    let tzid = params
+        .iter()
+        .find(|(name, _)| name == "TZID")
+        .map(|(_, value)| value.trim_start_matches('/'));
+    let parse =
+        |value| NaiveDateTime::parse_from_str(value, LOCAL_FORMAT).map_err(|_| Error::InvalidDate);
+    if let Some(utc) = value.strip_suffix('Z') {
+        return Ok(Due::DateTime(
+            Utc.from_utc_datetime(&parse(utc)?).fixed_offset(),
+        ));
+    }
+
+    let naive = parse(value)?;
+    let datetime = match tzid {
+        Some("UTC" | "Etc/UTC" | "GMT" | "Etc/GMT") => {
+            Some(Utc.from_utc_datetime(&naive).fixed_offset())
+        }
+        Some(tzid) if zones.contains_key(tzid) => zone_offset(&zones[tzid], naive)
+            .and_then(|offset| offset.from_local_datetime(&naive).single()),
+        Some(tzid) if !is_local_zone(tzid) => None,
+        _ => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|datetime| datetime.fixed_offset()),
+    };
+
+    Ok(datetime.map_or(Due::Date(naive.date()), Due::DateTime))
+}
+
+// The observances of each VTIMEZONE in a file, by TZID.
+type Zones = HashMap<String, Vec<Observance>>;
+
+// A STANDARD or DAYLIGHT part of a VTIMEZONE: the offset that applies from
+// its start, and again every year if it has a yearly rule.
+#[derive(Default)]
+struct Observance {
+    start: NaiveDateTime,
+    offset: Option<FixedOffset>,
+    rule: Option<(u32, i8, Weekday)>,
+}
+
+fn time_zones(lines: &[(usize, String)]) -> Zones {
+    let mut zones = Zones::new();
+    let mut tzid = None;
+    let mut observance: Option<Observance> = None;
+
+    for (_, line) in lines {
+        let (name, _, value) = match split_line(line) {
+            Some(parts) => parts,
+            None => continue,
+        };
+        let value = value.trim();
+        match (name.as_str(), value.to_uppercase().as_str()) {
+            ("BEGIN", "STANDARD" | "DAYLIGHT") => observance = Some(Observance::default()),
+            ("END", "STANDARD" | "DAYLIGHT") => {
+                if let (Some(tzid), Some(observance)) = (&tzid, observance.take()) {
+                    if observance.offset.is_some() {
+                        zones
+                            .entry(String::from(tzid))
+                            .or_default()
+                            .push(observance);
+                    }
+                }
+            }
+            ("END", "VTIMEZONE") => tzid = None,
+            ("TZID", _) => tzid = Some(value.trim_start_matches('/').to_string()),
+            _ => {}
+        }
+
+        let observance = match &mut observance {
+            Some(observance) => observance,
+            None => continue,
+        };
+        match name.as_str() {
+            "DTSTART" => {
+                if let Ok(start) = NaiveDateTime::parse_from_str(value, LOCAL_FORMAT) {
+                    observance.start = start;
+                }
+            }
+            "TZOFFSETTO" => observance.offset = parse_offset(value),
+            "RRULE" => observance.rule = parse_zone_rule(value),
+            _ => {}
+        }
+    }
+
+    zones
+}
+
+// The offset in effect at the local time `naive`: that of the observance that
+// started last before it.
+fn zone_offset(observances: &[Observance], naive: NaiveDateTime) -> Option<FixedOffset> {
+    let onsets = observances.iter().flat_map(|observance| {
+        let onsets = match observance.rule {
+            Some((month, week, weekday)) => [naive.year() - 1, naive.year()]
+                .into_iter()
+                .filter(|year| *year >= observance.start.year())
+                .filter_map(|year| nth_weekday(year, month, week, weekday))
+                .map(|date| date.and_time(observance.start.time()))
+                .collect(),
+            None => vec![observance.start],
+        };
+        onsets
+            .into_iter()
+            .filter(|onset| *onset <= naive)
+            .map(move |onset| (onset, observance.offset))
+    });
+
+    match onsets.max_by_key(|(onset, _)| *onset) {
+        Some((_, offset)) => offset,
+        None => {
+            observances
+                .iter()
+                .min_by_key(|observance| observance.start)?
+                .offset
+        }
+    }
+}
+
+// An offset such as `+0200` or `-053000`.
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let sign = match value.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = &value[1..];
+    if !matches!(digits.len(), 4 | 6) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let part = |i: usize| {
+        digits
+            .get(i..i + 2)
+            .map_or(Some(0), |part| part.parse::<i32>().ok())
+    };
+
+    FixedOffset::east_opt(sign * (part(0)? * 3600 + part(2)? * 60 + part(4)?))
+}
+
+// The yearly rules time zones change by, such as
+// `FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU` for the last Sunday of October.
+fn parse_zone_rule(value: &str) -> Option<(u32, i8, Weekday)> {
+    let mut month = None;
+    let mut day = None;
+    for part in value.to_uppercase().split(';') {
+        match part.split_once('=')? {
+            ("FREQ", frequency) if frequency != "YEARLY" => return None,
+            ("BYMONTH", value) => month = value.parse().ok(),
+            ("BYDAY", value) => {
+                let split = value.len().checked_sub(2)?;
+                let weekday = match value.get(split..)? {
+                    "MO" => Weekday::Mon,
+                    "TU" => Weekday::Tue,
+                    "WE" => Weekday::Wed,
+                    "TH" => Weekday::Thu,
+                    "FR" => Weekday::Fri,
+                    "SA" => Weekday::Sat,
+                    "SU" => Weekday::Sun,
+                    _ => return None,
+                };
+                day = Some((
+                    value.get(..split)?.trim_start_matches('+').parse().ok()?,
+                    weekday,
+                ));
+            }
+            _ => {}
+        }
+    }
+    let (week, weekday) = day?;
+
+    Some((month?, week, weekday))
+}
+
+// The `week`th `weekday` of a month, counting from its end when negative.
+fn nth_weekday(year: i32, month: u32, week: i8, weekday: Weekday) -> Option<NaiveDate> {
+    if week > 0 {
+        return NaiveDate::from_weekday_of_month_opt(year, month, weekday, week.try_into().ok()?);
+    }
+
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    let back = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    let weeks = u64::try_from(week.checked_neg()?.checked_sub(1)?).ok()?;
+
+    last.checked_sub_days(Days::new(u64::from(back) + 7 * weeks))
+        .filter(|date| date.month() == month)
+}
+
+fn is_local_zone(tzid: &str) -> bool {
+    iana_time_zone::get_timezone().is_ok_and(|local| local == tzid)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+// Splits a comma separated list value on the commas that aren't escaped.
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ',' => {
+                items.push(unescape(&value[start..i]).trim().to_string());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(unescape(&value[start..]).trim().to_string());
+
+    items
+}
+
+// Breaks a content line into lines of at most `LINE_LIMIT` bytes, without
+// splitting characters, each continuation starting with a space.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+// Joins folded lines back together, numbering each by the line it starts on.
+fn unfold(contents: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ if line.is_empty() => {}
+            _ => lines.push((i + 1, line.to_string())),
+        }
+    }
+
+    lines
+}
+
+type Params = Vec<(String, String)>;
+
+// Splits `NAME;PARAM=VALUE:value` into its upper-cased name, its parameters
+// and its value. Colons inside quoted parameter values don't count.
+fn split_line(line: &str) -> Option<(String, Params, String)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next().filter(|name| !name.is_empty())?.to_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| (name.to_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+
+    Some((name, params, value.to_string()))
+}
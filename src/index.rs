@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    date::{Due, DueFilter},
+    error::Error,
+    query::{Comparison, Condition, Query},
+    store::{Batch, Column, TodoStore},
+    todo::{Status, Todo},
+};
+
+/// Bumped whenever the index layout changes, so existing stores get
+/// reindexed the next time they are queried.
+const INDEX_VERSION: u32 = 1;
+const INDEX_VERSION_KEY: &[u8] = b"index_version";
+
+const INDEXES: [Column; 3] = [Column::ByTag, Column::ByStatus, Column::ByDue];
+
+// Dates without a time are indexed at the end of their local day, which moves
+// with the time zone, so due date lookups are widened by two days either way.
+// Candidates are matched against the full query afterwards.
+const DUE_SLACK_SECONDS: i64 = 2 * 24 * 60 * 60;
+
+type Names = BTreeSet<Vec<u8>>;
+
+// Tag and status keys are `<value>\0<name>`; due keys are the deadline as an
+// order-preserving 8 byte timestamp followed by the name.
+fn prefix_key(value: &str) -> Vec<u8> {
+    [value.as_bytes(), &[0]].concat()
+}
+
+fn status_key(status: Status) -> Vec<u8> {
+    prefix_key(&format!("{:?}", status))
+}
+
+fn due_key(due: &Due) -> [u8; 8] {
+    timestamp_key(due.deadline().timestamp())
+}
+
+fn timestamp_key(timestamp: i64) -> [u8; 8] {
+    ((timestamp as u64) ^ (1 << 63)).to_be_bytes()
+}
+
+fn entries(todo: &Todo) -> Vec<(Column, Vec<u8>)> {
+    let name = todo.name.as_bytes();
+
+    let mut entries: Vec<(Column, Vec<u8>)> = todo
+        .tags
+        .iter()
+        .map(|tag| (Column::ByTag, [prefix_key(tag).as_slice(), name].concat()))
+        .collect();
+    entries.push((
+        Column::ByStatus,
+        [status_key(todo.status).as_slice(), name].concat(),
+    ));
+    if let Some(due) = &todo.due_date {
+        entries.push((Column::ByDue, [due_key(due).as_slice(), name].concat()));
+    }
+
+    entries
+}
+
+/// Adds the index entries for `todo` to `batch`.
+pub(crate) fn stage(batch: &mut Batch, todo: &Todo) {
+    for (column, key) in entries(todo) {
+        batch.put(column, &key, &[]);
+    }
+}
+
+/// Adds deletes for the index entries of `todo` to `batch`.
+pub(crate) fn unstage(batch: &mut Batch, todo: &Todo) {
+    for (column, key) in entries(todo) {
+        batch.delete(column, &key);
+    }
+}
+
+pub(crate) fn is_current<S: TodoStore>(db: &S) -> Result<bool, Error> {
+    let version = db.get(Column::Meta, INDEX_VERSION_KEY)?;
+    Ok(version.as_deref() == Some(INDEX_VERSION.to_be_bytes().as_slice()))
+}
+
+/// Adds writes to `batch` that replace every index entry with ones built from
+/// `todos`.
+pub(crate) fn rebuild<S: TodoStore>(
+    db: &S,
+    batch: &mut Batch,
+    todos: &[Todo],
+) -> Result<(), Error> {
+    for column in INDEXES {
+        for (key, _) in db.scan(column)? {
+            batch.delete(column, &key);
+        }
+    }
+    for todo in todos {
+        stage(batch, todo);
+    }
+    batch.put(
+        Column::Meta,
+        INDEX_VERSION_KEY,
+        &INDEX_VERSION.to_be_bytes(),
+    );
+
+    Ok(())
+}
+
+/// The names of the todos that can match `query`, narrowed down by its status,
+/// tag and due date conditions, or `None` when it has none of those.
+pub(crate) fn candidates<S: TodoStore>(
+    db: &S,
+    query: &Query,
+    now: DateTime<Local>,
+) -> Result<Option<Names>, Error> {
+    let mut candidates: Option<Names> = None;
+
+    for condition in &query.conditions {
+        let names = match condition {
+            Condition::Status(status) => {
+                names_with_prefix(db, Column::ByStatus, &status_key(*status))?
+            }
+            Condition::Tags(tags) => {
+                let mut names = Names::new();
+                for tag in tags {
+                    names.extend(names_with_prefix(db, Column::ByTag, &prefix_key(tag))?);
+                }
+                names
+            }
+            Condition::Due(comparison, target) => {
+                let target = target.deadline().timestamp();
+                let (from, to) = match comparison {
+                    Comparison::Less | Comparison::LessOrEqual => (None, Some(target)),
+                    Comparison::Greater | Comparison::GreaterOrEqual => (Some(target), None),
+                    Comparison::Equal => (Some(target), Some(target)),
+                };
+                names_due_between(db, from, to)?
+            }
+            Condition::DueIs(filter) => {
+                let now = now.timestamp();
+                let (from, to) = match filter {
+                    DueFilter::Overdue => (None, Some(now)),
+                    DueFilter::Today => (Some(now), Some(now)),
+                    DueFilter::Within(within) => (Some(now), Some(now + within.num_seconds())),
+                };
+                names_due_between(db, from, to)?
+            }
+            _ => continue,
+        };
+
+        candidates = Some(match candidates {
+            Some(candidates) => candidates.intersection(&names).cloned().collect(),
+            None => names,
+        });
+    }
+
+    Ok(candidates)
+}
+
+fn names_with_prefix<S: TodoStore>(db: &S, column: Column, prefix: &[u8]) -> Result<Names, Error> {
+    Ok(db
+        .scan_prefix(column, prefix)?
+        .into_iter()
+        .map(|(key, _)| key[prefix.len()..].to_vec())
+        .collect())
+}
+
+fn names_due_between<S: TodoStore>(
+    db: &S,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Names, Error> {
+    let from = from.map_or(i64::MIN, |from| from.saturating_sub(DUE_SLACK_SECONDS));
+    // The range ends before the first timestamp past `to`.
+    let to = to.and_then(|to| to.saturating_add(DUE_SLACK_SECONDS).checked_add(1));
+    let (from, to) = (timestamp_key(from), to.map(timestamp_key));
+
+    Ok(db
+        .scan_range(Column::ByDue, &from, to.as_ref().map(|to| to.as_slice()))?
+        .into_iter()
+        .filter(|(key, _)| key.len() >= 8)
+        .map(|(key, _)| key[8..].to_vec())
+        .collect())
+}
@@ -1,10 +1,34 @@
 mod cli;
+pub mod config;
+pub mod date;
+pub mod error;
+pub mod ical;
+mod index;
+pub mod location;
+pub mod markdown;
+pub mod output;
+pub mod query;
+pub mod recurrence;
+pub mod search;
+pub mod sort;
+pub mod store;
 pub mod todo;
+pub mod todotxt;
+pub mod transfer;
 
 pub use cli::cli;
 
+pub use error::Error;
+pub use output::{write_todo, write_todos, OutputFormat};
+pub use query::{parse_query, Comparison, Condition, Query};
+pub use recurrence::{Frequency, Recurrence};
+pub use search::{search_todos, Matcher, SearchHit, SearchMode};
+pub use sort::{parse_sort, sort_todos, SortField, SortKey};
+pub use store::{Batch, MemoryStore, StoreError, TodoStore};
 pub use todo::{
     add_due_date, add_todo, add_todo_note, add_todo_tag, change_due_date, complete_todo,
-    delete_todo, drop_db, edit_todo_note, get_all_todos, remove_due_date, remove_todo_note,
-    remove_todo_tag, uncomplete_todo, Status,
+    delete_todo, drop_db, edit_todo_note, filter_by_due, find_todos, get_all_todos, get_todo,
+    import_checklist, import_todos, migrate_db, remove_due_date, remove_todo_note, remove_todo_tag,
+    rename_todo, set_priority, set_recurrence, set_status, uncomplete_todo, Completion, ImportMode,
+    ImportReport, Priority, Status, Todo,
 };
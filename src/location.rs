@@ -0,0 +1,126 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::Error;
+
+/// The environment variable that points `alle` at a database.
+pub const DB_ENV: &str = "ALLE_DB";
+
+/// The name of a project's own database, or of a file holding its path.
+pub const LOCAL_DB: &str = ".alle";
+
+/// Works out where the database lives from `--db`, `--list-name`, the
+/// `ALLE_DB` environment variable, the project around the working directory
+/// unless `global` is set, the config file's `configured` database and the
+/// platform's data directory.
+pub fn db_path(
+    db: Option<&Path>,
+    list_name: Option<&str>,
+    global: bool,
+    configured: Option<&Path>,
+) -> Result<PathBuf, Error> {
+    let cwd = match global {
+        true => None,
+        false => env::current_dir().ok(),
+    };
+
+    resolve_db_path(
+        db,
+        list_name,
+        env::var_os(DB_ENV).map(PathBuf::from),
+        cwd.as_deref(),
+        configured,
+        dirs::data_dir(),
+        dirs::home_dir(),
+    )
+}
+
+/// The path `init` creates a project database at.
+pub fn local_db_path() -> Result<PathBuf, Error> {
+    Ok(env::current_dir()?.join(LOCAL_DB))
+}
+
+/// Picks the database path, in order of precedence: an explicit `db`, the
+/// named list `list_name`, `env_db`, the nearest project database above `cwd`,
+/// the `configured` database and finally the default list in `data_dir`.
+/// Databases created by older versions in `~/.alle` keep being used until the
+/// default one exists.
+pub fn resolve_db_path(
+    db: Option<&Path>,
+    list_name: Option<&str>,
+    env_db: Option<PathBuf>,
+    cwd: Option<&Path>,
+    configured: Option<&Path>,
+    data_dir: Option<PathBuf>,
+    home_dir: Option<PathBuf>,
+) -> Result<PathBuf, Error> {
+    if let Some(db) = db {
+        return Ok(db.to_path_buf());
+    }
+    if let Some(list_name) = list_name {
+        validate_list_name(list_name)?;
+        return Ok(lists_dir(data_dir)?.join(list_name));
+    }
+    if let Some(env_db) = env_db.filter(|path| !path.as_os_str().is_empty()) {
+        return Ok(env_db);
+    }
+    if let Some(local) = cwd.map(|cwd| find_local_db(cwd, home_dir.as_deref())) {
+        if let Some(local) = local? {
+            return Ok(local);
+        }
+    }
+    if let Some(configured) = configured {
+        return Ok(configured.to_path_buf());
+    }
+
+    let default = lists_dir(data_dir)?.join("default");
+    if let Some(legacy) = home_dir.map(|home| home.join(".alle")) {
+        if legacy.is_dir() && !default.exists() {
+            return Ok(legacy);
+        }
+    }
+
+    Ok(default)
+}
+
+/// Walks up from `dir` looking for a `.alle` database, as git does for `.git`.
+/// A `.alle` file instead holds the path of the database, relative to the
+/// directory it is in. The search stops at `home_dir`, as `~/.alle` is the
+/// global database.
+pub fn find_local_db(dir: &Path, home_dir: Option<&Path>) -> Result<Option<PathBuf>, Error> {
+    for dir in dir.ancestors() {
+        if Some(dir) == home_dir {
+            break;
+        }
+
+        let candidate = dir.join(LOCAL_DB);
+        if candidate.is_dir() {
+            return Ok(Some(candidate));
+        }
+        if candidate.is_file() {
+            let target = fs::read_to_string(&candidate)?;
+            return Ok(Some(dir.join(target.trim())));
+        }
+    }
+
+    Ok(None)
+}
+
+fn lists_dir(data_dir: Option<PathBuf>) -> Result<PathBuf, Error> {
+    data_dir.map(|dir| dir.join("alle")).ok_or(Error::NoDataDir)
+}
+
+// List names become directory names, so they must stay a single component.
+fn validate_list_name(name: &str) -> Result<(), Error> {
+    let invalid = name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\'])
+        || name.chars().any(char::is_control);
+    match invalid {
+        true => Err(Error::InvalidListName(name.to_string())),
+        false => Ok(()),
+    }
+}
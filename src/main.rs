@@ -1,139 +1,483 @@
+use alle::{
+    add_due_date, add_todo, add_todo_note, add_todo_tag, change_due_date, cli, complete_todo,
+    config::{self, Config, TableColumn, TableStyle},
+    date::{self, DueFilter},
+    delete_todo, drop_db, edit_todo_note, find_todos, get_todo, import_checklist, import_todos,
+    location, markdown, migrate_db,
+    output::{write_import_report, write_migrated},
+    parse_query, parse_sort, remove_due_date, remove_todo_note, remove_todo_tag, rename_todo,
+    search_todos, set_priority, set_recurrence, set_status, sort_todos, store,
+    transfer::{self, Format},
+    uncomplete_todo, write_todo, write_todos, Comparison, Condition, Error, ImportMode,
+    OutputFormat, Query, SearchHit, SearchMode, Todo,
+};
+use chrono::{Duration, Local};
+use clap::{parser::ValueSource, ArgMatches};
 use comfy_table::{
     modifiers::{UTF8_ROUND_CORNERS, UTF8_SOLID_INNER_BORDERS},
-    presets::UTF8_FULL,
+    presets::{ASCII_FULL, ASCII_MARKDOWN, NOTHING, UTF8_FULL, UTF8_FULL_CONDENSED},
     Cell, Color, Table,
 };
-use rocksdb::DB;
-use alle::{
-    add_due_date, add_todo, add_todo_note, add_todo_tag, change_due_date, cli, complete_todo,
-    delete_todo, drop_db, edit_todo_note, get_all_todos, remove_due_date, remove_todo_note,
-    remove_todo_tag, uncomplete_todo, Status,
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, IsTerminal, Write},
+    ops::Range,
+    path::PathBuf,
+    process,
 };
 
-fn main() {
-    let binding = dirs::home_dir().unwrap();
-    let path = binding.to_str().unwrap().to_string() + "/.alle";
-    let db = DB::open_default(&path).unwrap();
+// Todos due within this many days are highlighted in the list.
+const DUE_SOON_DAYS: i64 = 3;
+
+// The `list` arguments that select todos.
+const LIST_FILTERS: [&str; 7] = [
+    "query",
+    "status",
+    "tag",
+    "priority",
+    "overdue",
+    "due_today",
+    "due_within",
+];
 
+fn main() {
     let matches = cli().get_matches();
+    let config = config::load().unwrap_or_else(|e| fail(e));
+    let output = *matches.get_one::<OutputFormat>("output").expect("default");
 
-    match matches.subcommand() {
-        Some(("add", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
+    let db_path = matches.get_one::<PathBuf>("db");
+    let list_name = matches.get_one::<String>("list_name");
+    let global = matches.get_flag("global");
+    let path = match matches.subcommand_name() {
+        Some("init") => location::local_db_path(),
+        _ => location::db_path(
+            db_path.map(PathBuf::as_path),
+            list_name.map(String::as_str),
+            global,
+            config.database.as_deref(),
+        ),
+    };
+    let path = path.unwrap_or_else(|e| fail(e));
 
-            let note = sub_matches.get_one::<String>("note");
-            let due = sub_matches.get_one::<String>("due");
+    let db = match store::open(&path) {
+        Ok(db) => db,
+        Err(e) => fail(Error::Storage(e)),
+    };
 
-            if let Err(e) = add_todo(&db, &key, note, due) {
-                println!("{}", e);
-            }
+    let result = match matches.subcommand() {
+        Some(("init", _)) => {
+            println!("Initialized todo list in {}", path.display());
+            Ok(())
         }
         Some(("list", sub_matches)) => {
-            let status_filter = sub_matches.get_one::<String>("status");
-
-            let tag_filter = sub_matches.get_one::<String>("tag");
-            let mut table = Table::new();
-            table
-                .load_preset(UTF8_FULL)
-                .apply_modifier(UTF8_SOLID_INNER_BORDERS)
-                .apply_modifier(UTF8_ROUND_CORNERS)
-                .set_header(vec!["Name", "Status", "Due Date", "Note", "Tags"]);
-
-            let todos = get_all_todos(&db, status_filter, tag_filter);
-            for todo in todos {
-                let status = match todo.status {
-                    Status::ToDo => Cell::new(todo.status.to_string()).fg(Color::Red),
-                    Status::Done => Cell::new(todo.status.to_string()).fg(Color::Green),
-                };
-                table.add_row(vec![
-                    Cell::new(todo.name),
-                    status,
-                    Cell::new(todo.due_date),
-                    Cell::new(todo.note),
-                    Cell::new(todo.tags.join(", ")),
-                ]);
-            }
-
-            println!("{table}");
-        }
-        Some(("complete", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            if let Err(e) = complete_todo(&db, &key) {
-                println!("{}", e);
-            }
+            let sort = sub_matches
+                .get_one::<String>("sort")
+                .or(config.list.sort.as_ref());
+            let group_by_tag = sub_matches.get_flag("group_by_tag");
+            list_query(sub_matches, &config)
+                .and_then(|query| list_todos(&db, &query, sort, output, group_by_tag, &config))
         }
-        Some(("uncomplete", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            if let Err(e) = uncomplete_todo(&db, &key) {
-                println!("{}", e);
-            }
-        }
-        Some(("add-note", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            let note = sub_matches.get_one::<String>("NOTE").expect("required");
-            if let Err(e) = add_todo_note(&db, &key, &note) {
-                println!("{}", e);
-            }
-        }
-        Some(("edit-note", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            let new_note = sub_matches.get_one::<String>("NOTE").expect("required");
-            if let Err(e) = edit_todo_note(&db, &key, &new_note) {
-                println!("{}", e);
-            }
-        }
-        Some(("remove-note", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            if let Err(e) = remove_todo_note(&db, &key) {
-                println!("{}", e);
-            }
-        }
-        Some(("add-tag", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            let tag = sub_matches.get_one::<String>("TAG").expect("required");
-            if let Err(e) = add_todo_tag(&db, &key, &tag) {
-                println!("{}", e);
-            }
-        }
-        Some(("remove-tag", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            let tag = sub_matches.get_one::<String>("TAG").expect("required");
-            if let Err(e) = remove_todo_tag(&db, &key, &tag) {
-                println!("{}", e);
-            }
+        Some(("search", sub_matches)) => {
+            let pattern = sub_matches.get_one::<String>("PATTERN").expect("required");
+            let mode = if sub_matches.get_flag("regex") {
+                SearchMode::Regex
+            } else if sub_matches.get_flag("fuzzy") {
+                SearchMode::Fuzzy
+            } else {
+                SearchMode::Substring
+            };
+
+            search_todos(&db, pattern, mode).and_then(|hits| match output {
+                OutputFormat::Table => {
+                    print_search_hits(hits, &config);
+                    Ok(())
+                }
+                _ => {
+                    let todos: Vec<Todo> = hits.into_iter().map(|hit| hit.todo).collect();
+                    write_todos(io::stdout(), &todos, output)
+                }
+            })
         }
-        Some(("add-due-date", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            let date = sub_matches.get_one::<String>("DATE").expect("required");
-            if let Err(e) = add_due_date(&db, &key, &date) {
-                println!("{}", e);
-            }
+        Some(("history", sub_matches)) => {
+            let key = sub_matches.get_one::<String>("TODO").expect("required");
+            get_todo(&db, key).and_then(|todo| match output {
+                OutputFormat::Table => {
+                    print_history(todo, &config);
+                    Ok(())
+                }
+                _ => write_todo(io::stdout(), &todo, output),
+            })
         }
-        Some(("change-due-date", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            let date = sub_matches.get_one::<String>("DATE").expect("required");
-            if let Err(e) = change_due_date(&db, &key, &date) {
-                println!("{}", e);
+        Some(("migrate", _)) => migrate_db(&db).and_then(|migrated| match output {
+            OutputFormat::Table | OutputFormat::Markdown => {
+                println!("Migrated {} todos", migrated);
+                Ok(())
             }
+            _ => write_migrated(io::stdout(), migrated, output),
+        }),
+        Some(("drop-db", _)) => drop_db(&path),
+        Some(("export", sub_matches)) => export(&db, sub_matches),
+        Some(("import", sub_matches)) => import(&db, sub_matches, output),
+        Some((command, sub_matches)) => {
+            change_todo(&db, command, sub_matches, &config).and_then(|todo| match output {
+                OutputFormat::Table => Ok(()),
+                _ => write_todo(io::stdout(), &todo, output),
+            })
         }
-        Some(("remove-due-date", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
-            if let Err(e) = remove_due_date(&db, &key) {
-                println!("{}", e);
-            }
+        _ => unreachable!(),
+    };
+
+    if let Err(e) = result {
+        fail(e);
+    }
+}
+
+// Runs one of the commands that change a todo, returning the todo as it was
+// saved, or as it was before it was deleted.
+fn change_todo(
+    db: &store::Db,
+    command: &str,
+    matches: &ArgMatches,
+    config: &Config,
+) -> Result<Todo, Error> {
+    let arg = |id: &str| matches.get_one::<String>(id).expect("required");
+
+    match command {
+        "add" => {
+            let note = matches.get_one::<String>("note");
+            let due = matches
+                .get_one::<String>("due")
+                .map(|due| config.dates.normalize(due));
+            let priority = matches.get_one::<String>("priority");
+            let repeat = matches.get_one::<String>("repeat");
+
+            add_todo(db, arg("NAME"), note, due.as_ref(), priority, repeat)
         }
-        Some(("delete", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("NAME").expect("required");
+        "complete" => complete_todo(db, arg("TODO")),
+        "uncomplete" => uncomplete_todo(db, arg("TODO")),
+        "set-status" => set_status(db, arg("TODO"), arg("STATUS")),
+        "add-note" => add_todo_note(db, arg("TODO"), arg("NOTE")),
+        "edit-note" => edit_todo_note(db, arg("TODO"), arg("NOTE")),
+        "remove-note" => remove_todo_note(db, arg("TODO")),
+        "add-tag" => add_todo_tag(db, arg("TODO"), arg("TAG")),
+        "remove-tag" => remove_todo_tag(db, arg("TODO"), arg("TAG")),
+        "add-due-date" => add_due_date(db, arg("TODO"), &config.dates.normalize(arg("DATE"))),
+        "change-due-date" => change_due_date(db, arg("TODO"), &config.dates.normalize(arg("DATE"))),
+        "remove-due-date" => remove_due_date(db, arg("TODO")),
+        "set-priority" => set_priority(db, arg("TODO"), arg("PRIORITY")),
+        "set-recurrence" => set_recurrence(db, arg("TODO"), arg("RULE")),
+        "rename" => rename_todo(db, arg("TODO"), arg("NEW_NAME")),
+        "delete" => delete_todo(db, arg("TODO")),
+        _ => unreachable!(),
+    }
+}
 
-            if let Err(e) = delete_todo(&db, &key) {
-                println!("{}", e);
-            }
+fn export(db: &store::Db, matches: &ArgMatches) -> Result<(), Error> {
+    let file = matches.get_one::<PathBuf>("FILE");
+    let format = transfer_format(matches, file);
+
+    match file {
+        Some(file) => {
+            let mut writer = BufWriter::new(File::create(file)?);
+            transfer::export_todos(db, &mut writer, format)?;
+            writer.flush()?;
+            Ok(())
         }
-        Some(("drop-db", _)) => {
-            if let Err(e) = drop_db(&path) {
-                println!("{}", e);
-            }
+        None => transfer::export_todos(db, io::stdout().lock(), format),
+    }
+}
+
+fn import(db: &store::Db, matches: &ArgMatches, output: OutputFormat) -> Result<(), Error> {
+    let file = matches.get_one::<PathBuf>("FILE").expect("required");
+    let mode = *matches.get_one::<ImportMode>("mode").expect("default");
+
+    let (todos, format) = if file.as_os_str() == "-" {
+        let format = transfer_format(matches, None);
+        (transfer::read_todos(io::stdin().lock(), format)?, format)
+    } else {
+        let format = transfer_format(matches, Some(file));
+        let reader = BufReader::new(File::open(file)?);
+        (transfer::read_todos(reader, format)?, format)
+    };
+
+    let report = match format {
+        Format::Markdown => import_checklist(db, &todos, mode)?,
+        _ => import_todos(db, &todos, mode)?,
+    };
+    if !matches!(output, OutputFormat::Table | OutputFormat::Markdown) {
+        return write_import_report(io::stdout(), &report, output);
+    }
+
+    for name in &report.updated {
+        eprintln!("Updated existing todo {}", name);
+    }
+    for name in &report.skipped {
+        eprintln!("Skipped existing todo {}", name);
+    }
+    println!(
+        "Imported {} todos: {} added, {} updated, {} skipped",
+        todos.len(),
+        report.added.len(),
+        report.updated.len(),
+        report.skipped.len()
+    );
+
+    Ok(())
+}
+
+// The `--format` given to `export` or `import`, or else the one the file's
+// extension names, or else JSON.
+fn transfer_format(matches: &ArgMatches, file: Option<&PathBuf>) -> Format {
+    match matches.get_one::<Format>("format") {
+        Some(format) => *format,
+        None => file
+            .and_then(|file| Format::from_path(file))
+            .unwrap_or_default(),
+    }
+}
+
+fn list_todos(
+    db: &store::Db,
+    query: &Query,
+    sort: Option<&String>,
+    output: OutputFormat,
+    group_by_tag: bool,
+    config: &Config,
+) -> Result<(), Error> {
+    let sort_keys = match sort {
+        Some(sort) => parse_sort(sort)?,
+        None => Vec::new(),
+    };
+
+    let mut todos = find_todos(db, query)?;
+    sort_todos(&mut todos, &sort_keys);
+    match output {
+        OutputFormat::Table => print_todos(todos, config),
+        OutputFormat::Markdown => markdown::write_checklist(io::stdout(), &todos, group_by_tag)?,
+        _ => write_todos(io::stdout(), &todos, output)?,
+    }
+
+    Ok(())
+}
+
+// Combines the `list` filter flags and `--query` into a single query. The
+// configured default query is only used when none of them are given.
+fn list_query(matches: &ArgMatches, config: &Config) -> Result<Query, Error> {
+    let filtered = LIST_FILTERS
+        .iter()
+        .any(|id| matches.value_source(id) == Some(ValueSource::CommandLine));
+    let query = match filtered {
+        true => matches.get_one::<String>("query"),
+        false => config.list.query.as_ref(),
+    };
+    let mut query = match query {
+        Some(query) => parse_query(query, Local::now(), &config.dates)?,
+        None => Query::new(),
+    };
+
+    if let Some(status) = matches.get_one::<String>("status") {
+        query.push(Condition::Status(status.parse()?));
+    }
+    if let Some(tags) = matches.get_many::<String>("tag") {
+        let tags: Vec<&String> = tags.collect();
+        query.push_tags(&tags, matches.get_flag("any_tag"));
+    }
+    if let Some(priority) = matches.get_one::<String>("priority") {
+        query.push(Condition::Priority(Comparison::Equal, priority.parse()?));
+    }
+
+    if matches.get_flag("overdue") {
+        query.push(Condition::DueIs(DueFilter::Overdue));
+    }
+    if matches.get_flag("due_today") {
+        query.push(Condition::DueIs(DueFilter::Today));
+    }
+    if let Some(within) = matches.get_one::<String>("due_within") {
+        let within = date::parse_duration(within)?;
+        query.push(Condition::DueIs(DueFilter::Within(within)));
+    }
+
+    Ok(query)
+}
+
+fn print_todos(todos: Vec<Todo>, config: &Config) {
+    let mut table = todo_table(config);
+    for todo in todos {
+        let tags = todo.tags.join(", ");
+        table.add_row(todo_row(&todo, &todo.name, &todo.note, &tags, config));
+    }
+
+    println!("{table}");
+}
+
+fn print_search_hits(hits: Vec<SearchHit>, config: &Config) {
+    // Highlighting is left out when the output is piped somewhere else.
+    let highlight = io::stdout().is_terminal();
+
+    let mut table = todo_table(config);
+    for hit in hits {
+        let todo = &hit.todo;
+        let mut name = todo.name.clone();
+        let mut note = todo.note.clone();
+        let mut tags: Vec<String> = todo.tags.clone();
+        if highlight {
+            name = highlight_matches(&todo.name, &hit.name);
+            note = highlight_matches(&todo.note, &hit.note);
+            tags = (todo.tags.iter().zip(&hit.tags))
+                .map(|(tag, ranges)| highlight_matches(tag, ranges))
+                .collect();
         }
-        _ => unreachable!(),
+
+        table.add_row(todo_row(todo, &name, &note, &tags.join(", "), config));
+    }
+
+    println!("{table}");
+}
+
+fn highlight_matches(text: &str, ranges: &[Range<usize>]) -> String {
+    let mut highlighted = String::new();
+    let mut last = 0;
+    for range in ranges {
+        highlighted.push_str(&text[last..range.start]);
+        highlighted.push_str(&format!("\x1b[1;33m{}\x1b[0m", &text[range.clone()]));
+        last = range.end;
+    }
+    highlighted.push_str(&text[last..]);
+
+    highlighted
+}
+
+fn table(style: TableStyle, header: Vec<&str>) -> Table {
+    let mut table = Table::new();
+    match style {
+        TableStyle::Rounded => table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_SOLID_INNER_BORDERS)
+            .apply_modifier(UTF8_ROUND_CORNERS),
+        TableStyle::Full => table.load_preset(UTF8_FULL),
+        TableStyle::Condensed => table.load_preset(UTF8_FULL_CONDENSED),
+        TableStyle::Ascii => table.load_preset(ASCII_FULL),
+        TableStyle::Markdown => table.load_preset(ASCII_MARKDOWN),
+        TableStyle::Plain => table.load_preset(NOTHING),
+    };
+    table.set_header(header);
+
+    table
+}
+
+fn todo_table(config: &Config) -> Table {
+    let header = config.table.columns.iter().map(|c| c.header()).collect();
+    table(config.table.style, header)
+}
+
+fn colored(text: impl ToString, color: Option<Color>) -> Cell {
+    match color {
+        Some(color) => Cell::new(text).fg(color),
+        None => Cell::new(text),
+    }
+}
+
+// The name, note and tags are passed separately so search results can show
+// them highlighted.
+fn todo_row(todo: &Todo, name: &str, note: &str, tags: &str, config: &Config) -> Vec<Cell> {
+    let now = Local::now();
+    let due_soon = DueFilter::Within(Duration::days(DUE_SOON_DAYS));
+    let colors = &config.colors;
+    let date_format = &config.dates.output_format;
+
+    config
+        .table
+        .columns
+        .iter()
+        .map(|column| match column {
+            // Records written before IDs existed have none until they are next
+            // saved.
+            TableColumn::Id => match todo.id {
+                0 => Cell::new(""),
+                id => Cell::new(id),
+            },
+            TableColumn::Name => Cell::new(name),
+            TableColumn::Status => colored(todo.status, colors.status(todo.status)),
+            TableColumn::Priority => match todo.priority {
+                Some(p) => colored(p, colors.priority(p)),
+                None => Cell::new(""),
+            },
+            TableColumn::Due => match todo.due_date {
+                Some(due) if todo.status.is_closed() => Cell::new(due.format(date_format)),
+                Some(due) if DueFilter::Overdue.matches(&due, now) => {
+                    colored(due.format(date_format), colors.overdue)
+                }
+                Some(due) if due_soon.matches(&due, now) => {
+                    colored(due.format(date_format), colors.due_soon)
+                }
+                Some(due) => Cell::new(due.format(date_format)),
+                None => Cell::new(""),
+            },
+            TableColumn::Repeat => Cell::new(
+                todo.recurrence
+                    .map(|recurrence| recurrence.to_string())
+                    .unwrap_or_default(),
+            ),
+            TableColumn::Note => Cell::new(note),
+            TableColumn::Tags => Cell::new(tags),
+        })
+        .collect()
+}
+
+fn print_history(todo: Todo, config: &Config) {
+    let mut table = table(config.table.style, vec!["Due Date", "Completed"]);
+
+    for completion in todo.history {
+        table.add_row(vec![
+            completion
+                .due_date
+                .map(|due| due.format(&config.dates.output_format))
+                .unwrap_or_default(),
+            completion
+                .completed_at
+                .with_timezone(&Local)
+                .format(&format!("{} %H:%M", config.dates.output_format))
+                .to_string(),
+        ]);
+    }
+
+    println!("{table}");
+}
+
+fn fail(e: Error) -> ! {
+    eprintln!("{}", e);
+    process::exit(exit_code(&e));
+}
+
+fn exit_code(e: &Error) -> i32 {
+    match e {
+        Error::NotFound => 3,
+        Error::AlreadyExists => 4,
+        Error::NoteAlreadyExists => 5,
+        Error::TagAlreadyExists => 6,
+        Error::TagNotFound => 7,
+        Error::InvalidDate => 8,
+        Error::InvalidStatus(_) => 9,
+        Error::InvalidPriority(_) => 10,
+        Error::InvalidSort(_) => 11,
+        Error::Storage(_) => 12,
+        Error::Corrupt(_) => 13,
+        Error::UnsupportedVersion(_) => 14,
+        Error::Encode(_) => 15,
+        Error::Io(_) => 16,
+        Error::InvalidDuration(_) => 17,
+        Error::InvalidRecurrence(_) => 18,
+        Error::InvalidQuery(_) => 19,
+        Error::InvalidPattern(_) => 20,
+        Error::Conflict => 21,
+        Error::InvalidListName(_) => 22,
+        Error::NoDataDir => 23,
+        Error::InvalidConfig(_) => 24,
+        Error::InvalidFormat(_) => 25,
+        Error::InvalidImportMode(_) => 26,
+        Error::InvalidImport(_) => 27,
+        Error::NotCompleted => 28,
+        Error::InvalidExport(_) => 29,
+        Error::EmptyName => 30,
     }
 }
@@ -0,0 +1,133 @@
+use std::io::{BufRead, Write};
+
+use crate::{
+    error::Error,
+    todo::{Status, Todo},
+};
+
+/// Starts a checklist grouped by tag, so that reading it back tags items with
+/// the heading they are under. It doesn't show once the Markdown is rendered.
+pub const TAG_MARKER: &str = "<!-- alle: group-by-tag -->";
+
+fn checklist_item(todo: &Todo) -> String {
+    let mark = if todo.status.is_closed() { 'x' } else { ' ' };
+    format!("- [{}] {}", mark, todo.name)
+}
+
+/// Writes `todos` as a GitHub-style checklist, in the order given. Grouped by
+/// tag, todos are listed under a heading for each of their tags, with untagged
+/// ones first, after a [`TAG_MARKER`].
+pub fn write_checklist<W: Write>(
+    mut writer: W,
+    todos: &[Todo],
+    group_by_tag: bool,
+) -> Result<(), Error> {
+    if !group_by_tag {
+        for todo in todos {
+            writeln!(writer, "{}", checklist_item(todo))?;
+        }
+        return Ok(());
+    }
+
+    let mut tags: Vec<&String> = Vec::new();
+    for tag in todos.iter().flat_map(|todo| &todo.tags) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    writeln!(writer, "{}\n", TAG_MARKER)?;
+    let untagged: Vec<&Todo> = todos.iter().filter(|todo| todo.tags.is_empty()).collect();
+    for todo in &untagged {
+        writeln!(writer, "{}", checklist_item(todo))?;
+    }
+    for (i, tag) in tags.into_iter().enumerate() {
+        if i > 0 || !untagged.is_empty() {
+            writeln!(writer)?;
+        }
+        writeln!(writer, "## {}\n", tag)?;
+        for todo in todos.iter().filter(|todo| todo.tags.contains(tag)) {
+            writeln!(writer, "{}", checklist_item(todo))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the items of a checklist as todos that are done or still to do, and
+/// ignores other lines. After a [`TAG_MARKER`] items under a heading are tagged
+/// with it, and an item listed more than once gets the tags of every heading
+/// it is under.
+pub fn read_checklist<R: BufRead>(reader: R) -> Result<Vec<Todo>, Error> {
+    let mut todos: Vec<Todo> = Vec::new();
+    let mut headings_are_tags = false;
+    let mut heading: Option<String> = None;
+
+    for (line, text) in reader.lines().enumerate() {
+        let text = text?;
+        let text = text.trim();
+
+        if text == TAG_MARKER {
+            headings_are_tags = true;
+            continue;
+        }
+        if let Some(title) = parse_heading(text) {
+            heading =
+                Some(title.to_string()).filter(|title| headings_are_tags && !title.is_empty());
+            continue;
+        }
+        let (done, name) = match parse_item(text) {
+            Some(item) => item,
+            None => continue,
+        };
+        if name.is_empty() {
+            return Err(Error::InvalidImport(format!(
+                "line {}: the item has no name",
+                line + 1
+            )));
+        }
+
+        let index = match todos.iter().position(|todo| todo.name == name) {
+            Some(index) => index,
+            None => {
+                todos.push(Todo {
+                    name: name.to_string(),
+                    status: if done { Status::Done } else { Status::ToDo },
+                    ..Todo::default()
+                });
+                todos.len() - 1
+            }
+        };
+        if let Some(tag) = &heading {
+            if !todos[index].tags.contains(tag) {
+                todos[index].tags.push(tag.clone());
+            }
+        }
+    }
+
+    Ok(todos)
+}
+
+// The title of an ATX heading such as `## work`.
+fn parse_heading(line: &str) -> Option<&str> {
+    let title = line.trim_start_matches('#');
+    match line.len() - title.len() {
+        1..=6 if title.is_empty() || title.starts_with(' ') => Some(title.trim()),
+        _ => None,
+    }
+}
+
+// Whether a `- [x] name` item is checked, and its name.
+fn parse_item(line: &str) -> Option<(bool, &str)> {
+    let rest = line
+        .strip_prefix(['-', '*', '+'])?
+        .strip_prefix(' ')?
+        .trim_start();
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+
+    Some((done, rest[3..].trim()))
+}
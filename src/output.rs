@@ -0,0 +1,203 @@
+use std::{io::Write, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{
+    error::Error,
+    markdown,
+    todo::{ImportReport, Todo},
+};
+
+/// How commands print todos. `Table` is rendered by the binary; the others
+/// are written by [`write_todos`] and [`write_todo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Jsonl,
+    Csv,
+    Markdown,
+}
+
+impl OutputFormat {
+    pub fn names() -> &'static [&'static str] {
+        &["table", "json", "jsonl", "csv", "markdown"]
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" | "ndjson" => Ok(OutputFormat::Jsonl),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(Error::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+/// The columns of CSV output. Tags are joined with commas, those with a comma
+/// or quote of their own quoted as in a CSV field, and history is left out.
+pub const CSV_HEADER: [&str; 9] = [
+    "id",
+    "name",
+    "status",
+    "priority",
+    "due_date",
+    "recurrence",
+    "note",
+    "tags",
+    "created_at",
+];
+
+/// Writes `todos` as a JSON array, one JSON object per line, CSV rows or a
+/// Markdown checklist.
+pub fn write_todos<W: Write>(
+    mut writer: W,
+    todos: &[Todo],
+    format: OutputFormat,
+) -> Result<(), Error> {
+    match format {
+        OutputFormat::Table => {}
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, todos).map_err(Error::Encode)?;
+            writeln!(writer)?;
+        }
+        OutputFormat::Jsonl => {
+            for todo in todos {
+                serde_json::to_writer(&mut writer, todo).map_err(Error::Encode)?;
+                writeln!(writer)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            csv.write_record(CSV_HEADER).map_err(std::io::Error::from)?;
+            for todo in todos {
+                csv.write_record(csv_record(todo))
+                    .map_err(std::io::Error::from)?;
+            }
+            csv.flush()?;
+        }
+        OutputFormat::Markdown => markdown::write_checklist(writer, todos, false)?,
+    }
+
+    Ok(())
+}
+
+/// Writes a single todo, as a JSON object rather than an array for JSON.
+pub fn write_todo<W: Write>(mut writer: W, todo: &Todo, format: OutputFormat) -> Result<(), Error> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, todo).map_err(Error::Encode)?;
+            writeln!(writer)?;
+            Ok(())
+        }
+        _ => write_todos(writer, std::slice::from_ref(todo), format),
+    }
+}
+
+/// Writes what an import did: the report itself as JSON, and a line or row per
+/// todo saying whether it was added, updated or skipped as JSON Lines or CSV.
+pub fn write_import_report<W: Write>(
+    mut writer: W,
+    report: &ImportReport,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let rows = [
+        (&report.added, "added"),
+        (&report.updated, "updated"),
+        (&report.skipped, "skipped"),
+    ]
+    .into_iter()
+    .flat_map(|(names, result)| names.iter().map(move |name| (name, result)));
+
+    match format {
+        OutputFormat::Table | OutputFormat::Markdown => {}
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, report).map_err(Error::Encode)?;
+            writeln!(writer)?;
+        }
+        OutputFormat::Jsonl => {
+            for (name, result) in rows {
+                let row = serde_json::json!({ "name": name, "result": result });
+                serde_json::to_writer(&mut writer, &row).map_err(Error::Encode)?;
+                writeln!(writer)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            csv.write_record(["name", "result"])
+                .map_err(std::io::Error::from)?;
+            for (name, result) in rows {
+                csv.write_record([name, result])
+                    .map_err(std::io::Error::from)?;
+            }
+            csv.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes how many todos a migration upgraded, as `{"migrated": 3}` or a CSV
+/// column.
+pub fn write_migrated<W: Write>(
+    mut writer: W,
+    migrated: usize,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    match format {
+        OutputFormat::Table | OutputFormat::Markdown => {}
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let report = serde_json::json!({ "migrated": migrated });
+            serde_json::to_writer(&mut writer, &report).map_err(Error::Encode)?;
+            writeln!(writer)?;
+        }
+        OutputFormat::Csv => writeln!(writer, "migrated\n{}", migrated)?,
+    }
+
+    Ok(())
+}
+
+fn csv_record(todo: &Todo) -> [String; 9] {
+    [
+        todo.id.to_string(),
+        todo.name.clone(),
+        serde_name(&todo.status),
+        todo.priority.as_ref().map(serde_name).unwrap_or_default(),
+        todo.due_date.map(|due| due.to_string()).unwrap_or_default(),
+        todo.recurrence
+            .map(|recurrence| recurrence.to_string())
+            .unwrap_or_default(),
+        todo.note.clone(),
+        csv_tags(&todo.tags),
+        todo.created_at
+            .map(|created_at| created_at.to_rfc3339())
+            .unwrap_or_default(),
+    ]
+}
+
+fn csv_tags(tags: &[String]) -> String {
+    let tags: Vec<String> = tags
+        .iter()
+        .map(|tag| match tag.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", tag.replace('"', "\"\"")),
+            false => tag.clone(),
+        })
+        .collect();
+
+    tags.join(",")
+}
+
+// The name a unit variant is serialized with, so CSV matches the JSON output.
+fn serde_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
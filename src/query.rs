@@ -0,0 +1,222 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    config::Dates,
+    date::{self, Due, DueFilter},
+    error::Error,
+    todo::{Priority, Status, Todo},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn holds<T: Ord>(self, value: T, target: T) -> bool {
+        match self {
+            Comparison::Less => value < target,
+            Comparison::LessOrEqual => value <= target,
+            Comparison::Equal => value == target,
+            Comparison::GreaterOrEqual => value >= target,
+            Comparison::Greater => value > target,
+        }
+    }
+}
+
+/// A single test a todo has to pass to be selected by a [`Query`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Status(Status),
+    /// Has at least one of the tags.
+    Tags(Vec<String>),
+    Priority(Comparison, Priority),
+    NoPriority,
+    Due(Comparison, Due),
+    DueIs(DueFilter),
+    NoDue,
+    /// The name contains the text, ignoring case.
+    Name(String),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub fn matches(&self, todo: &Todo, now: DateTime<Local>) -> bool {
+        match self {
+            Condition::Status(status) => todo.status == *status,
+            Condition::Tags(tags) => tags.iter().any(|tag| todo.tags.contains(tag)),
+            Condition::Priority(comparison, priority) => todo
+                .priority
+                .is_some_and(|p| comparison.holds(p, *priority)),
+            Condition::NoPriority => todo.priority.is_none(),
+            Condition::Due(comparison, target) => todo
+                .due_date
+                .is_some_and(|due| compare_due(*comparison, due, *target)),
+            // Done and cancelled todos are never overdue or due soon, but
+            // still count as due on their day.
+            Condition::DueIs(filter) => match &todo.due_date {
+                Some(_) if todo.status.is_closed() && *filter != DueFilter::Today => false,
+                Some(due) => filter.matches(due, now),
+                None => false,
+            },
+            Condition::NoDue => todo.due_date.is_none(),
+            Condition::Name(text) => todo.name.to_lowercase().contains(&text.to_lowercase()),
+            Condition::Not(condition) => !condition.matches(todo, now),
+        }
+    }
+}
+
+// Whole-day targets compare by local date so `due<=2026-11-01` includes todos
+// due at any time that day.
+fn compare_due(comparison: Comparison, due: Due, target: Due) -> bool {
+    match target {
+        Due::Date(date) => comparison.holds(due.date(), date),
+        Due::DateTime(_) => comparison.holds(due.deadline(), target.deadline()),
+    }
+}
+
+/// A set of conditions that all have to hold for a todo to be selected.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub conditions: Vec<Condition>,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, condition: Condition) {
+        self.conditions.push(condition);
+    }
+
+    /// Adds tag conditions as given to `list -t`. Tags prefixed with `!` must
+    /// be absent; the others must all be present, or just one of them when
+    /// `any` is set.
+    pub fn push_tags<T: AsRef<str>>(&mut self, tags: &[T], any: bool) {
+        let mut wanted = Vec::new();
+        for tag in tags.iter().map(AsRef::as_ref) {
+            match tag.strip_prefix('!') {
+                Some(excluded) => self.push(Condition::Not(Box::new(Condition::Tags(vec![
+                    excluded.to_string(),
+                ])))),
+                None => wanted.push(tag.to_string()),
+            }
+        }
+
+        if any && !wanted.is_empty() {
+            self.push(Condition::Tags(wanted));
+        } else {
+            for tag in wanted {
+                self.push(Condition::Tags(vec![tag]));
+            }
+        }
+    }
+
+    pub fn matches(&self, todo: &Todo, now: DateTime<Local>) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.matches(todo, now))
+    }
+}
+
+/// Parses a query such as `status:todo tag:work|home due<2026-11-01
+/// priority>=high`. Terms are separated by spaces and all have to hold; a
+/// leading `!` negates a term and `|` separates alternative tags. Relative
+/// dates are resolved against `now`, and dates may also be given in the
+/// configured input format.
+pub fn parse_query(input: &str, now: DateTime<Local>, dates: &Dates) -> Result<Query, Error> {
+    let mut query = Query::new();
+    for term in tokenize(input)? {
+        query.push(parse_term(&term, now, dates)?);
+    }
+
+    Ok(query)
+}
+
+// Splits on whitespace outside double quotes, dropping the quotes.
+fn tokenize(input: &str) -> Result<Vec<String>, Error> {
+    let mut terms = Vec::new();
+    let mut term = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !term.is_empty() {
+                    terms.push(std::mem::take(&mut term));
+                }
+            }
+            c => term.push(c),
+        }
+    }
+    if quoted {
+        return Err(Error::InvalidQuery(input.to_string()));
+    }
+    if !term.is_empty() {
+        terms.push(term);
+    }
+
+    Ok(terms)
+}
+
+fn parse_term(term: &str, now: DateTime<Local>, dates: &Dates) -> Result<Condition, Error> {
+    let invalid = || Error::InvalidQuery(term.to_string());
+
+    if let Some(negated) = term.strip_prefix('!') {
+        return Ok(Condition::Not(Box::new(parse_term(negated, now, dates)?)));
+    }
+
+    let split = term.find([':', '=', '<', '>', '!']).ok_or_else(invalid)?;
+    let field = term[..split].to_lowercase();
+    let rest = &term[split..];
+    let (operator, value) = ["<=", ">=", "!=", ":", "=", "<", ">"]
+        .into_iter()
+        .find_map(|operator| rest.strip_prefix(operator).map(|value| (operator, value)))
+        .ok_or_else(invalid)?;
+    if value.is_empty() {
+        return Err(invalid());
+    }
+
+    let (comparison, negated) = match operator {
+        ":" | "=" => (Comparison::Equal, false),
+        "!=" => (Comparison::Equal, true),
+        "<" => (Comparison::Less, false),
+        "<=" => (Comparison::LessOrEqual, false),
+        ">" => (Comparison::Greater, false),
+        ">=" => (Comparison::GreaterOrEqual, false),
+        _ => unreachable!(),
+    };
+    let ordered = comparison != Comparison::Equal;
+
+    let condition = match field.as_str() {
+        "status" if !ordered => Condition::Status(Status::from_str(value)?),
+        "tag" | "tags" if !ordered => {
+            Condition::Tags(value.split('|').map(str::to_string).collect())
+        }
+        "name" if !ordered => Condition::Name(value.to_string()),
+        "priority" => match value.to_lowercase().as_str() {
+            "none" if !ordered => Condition::NoPriority,
+            _ => Condition::Priority(comparison, Priority::from_str(value)?),
+        },
+        "due" => match value.to_lowercase().as_str() {
+            "none" if !ordered => Condition::NoDue,
+            "overdue" if !ordered => Condition::DueIs(DueFilter::Overdue),
+            "today" if !ordered => Condition::DueIs(DueFilter::Today),
+            _ => Condition::Due(comparison, date::parse_due(&dates.normalize(value), now)?),
+        },
+        _ => return Err(invalid()),
+    };
+
+    match negated {
+        true => Ok(Condition::Not(Box::new(condition))),
+        false => Ok(condition),
+    }
+}
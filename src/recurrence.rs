@@ -0,0 +1,193 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Duration, Local, Months, NaiveDate, Offset, TimeZone};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{date::Due, error::Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// How often a todo repeats, e.g. every 2 weeks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+}
+
+impl Recurrence {
+    pub fn new(frequency: Frequency, interval: u32) -> Self {
+        Recurrence {
+            frequency,
+            interval,
+        }
+    }
+
+    /// The first occurrence after `due` that is not yet overdue at `now`, so
+    /// completing a todo that slipped several periods does not leave it behind.
+    pub fn next(&self, due: Due, now: DateTime<Local>) -> Option<Due> {
+        self.next_from(due, due, now)
+    }
+
+    /// Like [`Recurrence::next`], but counts the occurrences from `anchor`, the
+    /// due date the series started on, as long as `due` is one of them. That way
+    /// a todo due on the 31st comes back on the 31st after shorter months.
+    pub fn next_from(&self, anchor: Due, due: Due, now: DateTime<Local>) -> Option<Due> {
+        let (anchor, mut n) = match self.occurrence_index(anchor, due) {
+            Some(n) => (anchor, n + 1),
+            None => (due, 1),
+        };
+
+        let mut next = self.nth(anchor, n)?;
+        while next.is_overdue(now) {
+            n = n.checked_add(1)?;
+            next = self.nth(anchor, n)?;
+        }
+
+        Some(next)
+    }
+
+    // Which occurrence counted from `anchor` `due` is, if it is one.
+    fn occurrence_index(&self, anchor: Due, due: Due) -> Option<u32> {
+        let mut n = 0;
+        loop {
+            let occurrence = self.nth(anchor, n)?;
+            if occurrence == due {
+                return Some(n);
+            }
+            if occurrence.deadline() > due.deadline() {
+                return None;
+            }
+            n = n.checked_add(1)?;
+        }
+    }
+
+    // The occurrence `n` periods after `anchor`. Months are added to the
+    // anchor itself, so days past the end of a shorter month are only clamped
+    // for that month. A time in the local zone keeps its time of day across
+    // daylight saving changes; one with another offset keeps that offset.
+    fn nth(&self, anchor: Due, n: u32) -> Option<Due> {
+        match anchor {
+            Due::Date(date) => self.nth_date(date, n).map(Due::Date),
+            Due::DateTime(datetime) => {
+                let date = self.nth_date(datetime.date_naive(), n)?;
+                let days = date.signed_duration_since(datetime.date_naive());
+                let next = datetime.checked_add_signed(days)?;
+                if Local.offset_from_utc_datetime(&datetime.naive_utc()).fix() != *datetime.offset()
+                {
+                    return Some(Due::DateTime(next));
+                }
+
+                // A time skipped by the clocks going forward keeps the offset.
+                let local = Local.from_local_datetime(&next.naive_local()).earliest();
+                Some(Due::DateTime(
+                    local.map_or(next, |local| local.fixed_offset()),
+                ))
+            }
+        }
+    }
+
+    fn nth_date(&self, date: NaiveDate, n: u32) -> Option<NaiveDate> {
+        let periods = self.interval.checked_mul(n)?;
+        match self.frequency {
+            Frequency::Daily => date.checked_add_signed(Duration::days(periods.into())),
+            Frequency::Weekly => date.checked_add_signed(Duration::weeks(periods.into())),
+            Frequency::Monthly => date.checked_add_months(Months::new(periods)),
+            Frequency::Yearly => date.checked_add_months(Months::new(periods.checked_mul(12)?)),
+        }
+    }
+
+    // The RRULE subset alle understands: FREQ with an optional INTERVAL.
+    fn parse_rrule(rule: &str) -> Option<Self> {
+        let mut frequency = None;
+        let mut interval = 1;
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            match part.split_once('=')? {
+                ("freq", value) => frequency = Some(parse_frequency(value)?),
+                ("interval", value) => interval = value.parse().ok()?,
+                _ => return None,
+            }
+        }
+
+        Some(Recurrence::new(frequency?, interval))
+    }
+}
+
+/// Accepts `daily`, `weekly`, `monthly`, `yearly`, `every N days` (or weeks,
+/// months, years) and RRULEs such as `FREQ=WEEKLY;INTERVAL=2`.
+impl FromStr for Recurrence {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim().to_lowercase();
+        let invalid = || Error::InvalidRecurrence(s.to_string());
+
+        let recurrence = if let Some(rule) = input.strip_prefix("rrule:") {
+            Recurrence::parse_rrule(rule)
+        } else if input.starts_with("freq=") {
+            Recurrence::parse_rrule(&input)
+        } else if let Some(every) = input.strip_prefix("every ") {
+            match every.split_once(' ') {
+                Some((interval, unit)) => interval
+                    .parse()
+                    .ok()
+                    .zip(parse_frequency(unit.trim_end_matches('s')))
+                    .map(|(interval, frequency)| Recurrence::new(frequency, interval)),
+                None => parse_frequency(every).map(|frequency| Recurrence::new(frequency, 1)),
+            }
+        } else {
+            parse_frequency(&input).map(|frequency| Recurrence::new(frequency, 1))
+        };
+
+        match recurrence {
+            Some(recurrence) if recurrence.interval > 0 => Ok(recurrence),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn parse_frequency(input: &str) -> Option<Frequency> {
+    let frequency = match input {
+        "day" | "daily" => Frequency::Daily,
+        "week" | "weekly" => Frequency::Weekly,
+        "month" | "monthly" => Frequency::Monthly,
+        "year" | "yearly" => Frequency::Yearly,
+        _ => return None,
+    };
+
+    Some(frequency)
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (adverb, unit) = match self.frequency {
+            Frequency::Daily => ("daily", "days"),
+            Frequency::Weekly => ("weekly", "weeks"),
+            Frequency::Monthly => ("monthly", "months"),
+            Frequency::Yearly => ("yearly", "years"),
+        };
+
+        match self.interval {
+            1 => write!(f, "{}", adverb),
+            interval => write!(f, "every {} {}", interval, unit),
+        }
+    }
+}
+
+impl Serialize for Recurrence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Recurrence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
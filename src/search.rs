@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    error::Error,
+    query::Query,
+    store::TodoStore,
+    todo::{find_todos, Todo},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    #[default]
+    Substring,
+    Regex,
+    Fuzzy,
+}
+
+/// A search pattern compiled for one [`SearchMode`]. Matching ignores case.
+pub struct Matcher {
+    kind: MatcherKind,
+}
+
+enum MatcherKind {
+    Substring(Vec<char>),
+    Regex(Regex),
+    Fuzzy(Vec<char>),
+}
+
+impl Matcher {
+    pub fn new(pattern: &str, mode: SearchMode) -> Result<Self, Error> {
+        let kind = match mode {
+            SearchMode::Substring => MatcherKind::Substring(pattern.chars().collect()),
+            SearchMode::Fuzzy => {
+                MatcherKind::Fuzzy(pattern.chars().filter(|c| !c.is_whitespace()).collect())
+            }
+            SearchMode::Regex => RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(MatcherKind::Regex)
+                .map_err(|_| Error::InvalidPattern(pattern.to_string()))?,
+        };
+
+        Ok(Matcher { kind })
+    }
+
+    /// The byte ranges of `text` that match, or none if it does not match.
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        match &self.kind {
+            MatcherKind::Substring(pattern) => find_substring(text, pattern),
+            MatcherKind::Regex(regex) => regex
+                .find_iter(text)
+                .map(|m| m.range())
+                .filter(|range| !range.is_empty())
+                .collect(),
+            MatcherKind::Fuzzy(pattern) => find_fuzzy(text, pattern),
+        }
+    }
+}
+
+/// A todo found by [`search_todos`] with the matching parts of its name, note
+/// and each of its tags.
+#[derive(Debug)]
+pub struct SearchHit {
+    pub todo: Todo,
+    pub name: Vec<Range<usize>>,
+    pub note: Vec<Range<usize>>,
+    pub tags: Vec<Vec<Range<usize>>>,
+}
+
+/// Returns the todos whose name, note or tags match `pattern`.
+pub fn search_todos<S: TodoStore>(
+    db: &S,
+    pattern: &str,
+    mode: SearchMode,
+) -> Result<Vec<SearchHit>, Error> {
+    let matcher = Matcher::new(pattern, mode)?;
+
+    let mut hits = Vec::new();
+    for todo in find_todos(db, &Query::new())? {
+        let hit = SearchHit {
+            name: matcher.find(&todo.name),
+            note: matcher.find(&todo.note),
+            tags: todo.tags.iter().map(|tag| matcher.find(tag)).collect(),
+            todo,
+        };
+        if !hit.name.is_empty() || !hit.note.is_empty() || hit.tags.iter().any(|t| !t.is_empty()) {
+            hits.push(hit);
+        }
+    }
+
+    Ok(hits)
+}
+
+fn same_letter(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+fn find_substring(text: &str, pattern: &[char]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    if pattern.is_empty() {
+        return ranges;
+    }
+
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut start = 0;
+    while start + pattern.len() <= chars.len() {
+        let found = pattern
+            .iter()
+            .enumerate()
+            .all(|(i, c)| same_letter(chars[start + i].1, *c));
+        if !found {
+            start += 1;
+            continue;
+        }
+
+        let end = start + pattern.len();
+        let end_byte = chars.get(end).map_or(text.len(), |(byte, _)| *byte);
+        ranges.push(chars[start].0..end_byte);
+        start = end;
+    }
+
+    ranges
+}
+
+// Matches when the pattern's characters appear in order, taking the earliest
+// occurrence of each and merging adjacent ones into a single range.
+fn find_fuzzy(text: &str, pattern: &[char]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut pattern = pattern.iter().peekable();
+
+    for (byte, c) in text.char_indices() {
+        let wanted = match pattern.peek() {
+            Some(wanted) => **wanted,
+            None => break,
+        };
+        if !same_letter(c, wanted) {
+            continue;
+        }
+        pattern.next();
+
+        let end = byte + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == byte => last.end = end,
+            _ => ranges.push(byte..end),
+        }
+    }
+
+    match pattern.peek() {
+        Some(_) => Vec::new(),
+        None => ranges,
+    }
+}
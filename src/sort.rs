@@ -0,0 +1,106 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use crate::{
+    error::Error,
+    todo::{Status, Todo},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortField {
+    Due,
+    Priority,
+    Status,
+    Created,
+    Name,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, direction) = match s.split_once(':') {
+            Some((field, direction)) => (field, direction),
+            None => (s, "asc"),
+        };
+
+        let field = match field.trim().to_lowercase().as_str() {
+            "due" | "due_date" => SortField::Due,
+            "priority" => SortField::Priority,
+            "status" => SortField::Status,
+            "created" | "created_at" => SortField::Created,
+            "name" => SortField::Name,
+            _ => return Err(Error::InvalidSort(s.to_string())),
+        };
+        let descending = match direction.trim().to_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            _ => return Err(Error::InvalidSort(s.to_string())),
+        };
+
+        Ok(SortKey { field, descending })
+    }
+}
+
+/// Parses a comma separated list of sort keys such as `priority:desc,due`.
+pub fn parse_sort(spec: &str) -> Result<Vec<SortKey>, Error> {
+    spec.split(',')
+        .filter(|key| !key.trim().is_empty())
+        .map(SortKey::from_str)
+        .collect()
+}
+
+/// Sorts todos by each key in turn. Todos missing the sorted value (no due
+/// date, priority or creation time) always go last, whatever the direction.
+pub fn sort_todos(todos: &mut [Todo], keys: &[SortKey]) {
+    todos.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| compare(a, b, key))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+fn compare(a: &Todo, b: &Todo, key: &SortKey) -> Ordering {
+    let ordering = match key.field {
+        SortField::Due => {
+            let deadline = |todo: &Todo| todo.due_date.map(|due| due.deadline());
+            return compare_optional(deadline(a), deadline(b), key.descending);
+        }
+        SortField::Priority => return compare_optional(a.priority, b.priority, key.descending),
+        SortField::Created => return compare_optional(a.created_at, b.created_at, key.descending),
+        SortField::Status => status_rank(a).cmp(&status_rank(b)),
+        SortField::Name => a.name.cmp(&b.name),
+    };
+
+    if key.descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+fn compare_optional<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn status_rank(todo: &Todo) -> u8 {
+    match todo.status {
+        Status::ToDo => 0,
+        Status::InProgress => 1,
+        Status::Blocked => 2,
+        Status::Done => 3,
+        Status::Cancelled => 4,
+    }
+}
@@ -0,0 +1,429 @@
+use std::{
+    collections::BTreeMap,
+    error, fmt, fs,
+    ops::Bound,
+    path::Path,
+    sync::{Mutex, MutexGuard},
+};
+
+use rocksdb::{
+    ColumnFamily, Direction, ErrorKind, IteratorMode, OptimisticTransactionDB, Options,
+    SingleThreaded,
+};
+
+pub type Entry = (Vec<u8>, Vec<u8>);
+
+/// The RocksDB database `alle` keeps its todos in.
+pub type Db = OptimisticTransactionDB<SingleThreaded>;
+
+#[derive(Debug)]
+pub struct StoreError {
+    message: String,
+    conflict: bool,
+}
+
+impl StoreError {
+    pub fn new(message: impl Into<String>) -> Self {
+        StoreError {
+            message: message.into(),
+            conflict: false,
+        }
+    }
+
+    /// The error for a batch whose reads were invalidated by another writer.
+    pub fn conflict() -> Self {
+        StoreError {
+            message: String::from("the store was changed by another writer"),
+            conflict: true,
+        }
+    }
+
+    pub fn is_conflict(&self) -> bool {
+        self.conflict
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl error::Error for StoreError {}
+
+impl From<rocksdb::Error> for StoreError {
+    fn from(e: rocksdb::Error) -> Self {
+        match e.kind() {
+            ErrorKind::Busy | ErrorKind::TryAgain => StoreError::conflict(),
+            _ => StoreError::new(e.into_string()),
+        }
+    }
+}
+
+/// A separate keyspace within a store.
+///
+/// `Todos` holds the records keyed by name, `Meta` holds bookkeeping such as
+/// the ID counter and `Ids` maps each todo ID to its name. `ByTag`, `ByStatus`
+/// and `ByDue` are secondary indexes whose keys end with the todo's name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Column {
+    Todos,
+    Meta,
+    Ids,
+    ByTag,
+    ByStatus,
+    ByDue,
+}
+
+impl Column {
+    pub const ALL: [Column; 6] = [
+        Column::Todos,
+        Column::Meta,
+        Column::Ids,
+        Column::ByTag,
+        Column::ByStatus,
+        Column::ByDue,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Todos => rocksdb::DEFAULT_COLUMN_FAMILY_NAME,
+            Column::Meta => "meta",
+            Column::Ids => "ids",
+            Column::ByTag => "by_tag",
+            Column::ByStatus => "by_status",
+            Column::ByDue => "by_due",
+        }
+    }
+}
+
+pub enum BatchOp {
+    Put(Column, Vec<u8>, Vec<u8>),
+    Delete(Column, Vec<u8>),
+}
+
+/// A value a [`Batch`] was built from, which must be unchanged when it is
+/// written.
+pub struct Read {
+    pub column: Column,
+    pub key: Vec<u8>,
+    pub value: Option<Vec<u8>>,
+}
+
+/// A set of writes applied atomically by [`TodoStore::write`], along with the
+/// reads they depend on.
+#[derive(Default)]
+pub struct Batch {
+    ops: Vec<BatchOp>,
+    reads: Vec<Read>,
+}
+
+impl Batch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put(&mut self, column: Column, key: &[u8], value: &[u8]) {
+        self.ops
+            .push(BatchOp::Put(column, key.to_vec(), value.to_vec()));
+    }
+
+    pub fn delete(&mut self, column: Column, key: &[u8]) {
+        self.ops.push(BatchOp::Delete(column, key.to_vec()));
+    }
+
+    fn staged(&self, column: Column, key: &[u8]) -> Option<Option<&[u8]>> {
+        self.ops.iter().rev().find_map(|op| match op {
+            BatchOp::Put(c, k, v) if *c == column && k == key => Some(Some(v.as_slice())),
+            BatchOp::Delete(c, k) if *c == column && k == key => Some(None),
+            _ => None,
+        })
+    }
+
+    /// Reads `key` as this batch would leave it: the staged value if there is
+    /// one, otherwise the stored value, which the write then depends on.
+    pub fn read<S: TodoStore + ?Sized>(
+        &mut self,
+        db: &S,
+        column: Column,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, StoreError> {
+        if let Some(staged) = self.staged(column, key) {
+            return Ok(staged.map(<[u8]>::to_vec));
+        }
+        if let Some(read) = self
+            .reads
+            .iter()
+            .find(|read| read.column == column && read.key == key)
+        {
+            return Ok(read.value.clone());
+        }
+
+        let value = db.get(column, key)?;
+        self.expect(column, key, value.as_deref());
+        Ok(value)
+    }
+
+    /// Makes the write fail with a conflict unless `key` still holds `value`.
+    pub fn expect(&mut self, column: Column, key: &[u8], value: Option<&[u8]>) {
+        self.reads.push(Read {
+            column,
+            key: key.to_vec(),
+            value: value.map(<[u8]>::to_vec),
+        });
+    }
+
+    pub fn ops(&self) -> &[BatchOp] {
+        &self.ops
+    }
+
+    pub fn reads(&self) -> &[Read] {
+        &self.reads
+    }
+}
+
+/// A key-value backend for todo records.
+///
+/// Keys and values are raw bytes; `scan` returns every entry of a column in
+/// key order and `scan_prefix` only those whose key starts with `prefix`.
+/// `write` applies a batch only if none of its reads have changed since, and
+/// fails with a conflict otherwise.
+pub trait TodoStore {
+    fn get(&self, column: Column, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError>;
+    fn put(&self, column: Column, key: &[u8], value: &[u8]) -> Result<(), StoreError>;
+    fn delete(&self, column: Column, key: &[u8]) -> Result<(), StoreError>;
+    fn scan(&self, column: Column) -> Result<Vec<Entry>, StoreError>;
+    fn scan_prefix(&self, column: Column, prefix: &[u8]) -> Result<Vec<Entry>, StoreError>;
+    /// The entries with keys from `from` up to but not including `to`, or to
+    /// the end of the column without one.
+    fn scan_range(
+        &self,
+        column: Column,
+        from: &[u8],
+        to: Option<&[u8]>,
+    ) -> Result<Vec<Entry>, StoreError>;
+    fn write(&self, batch: Batch) -> Result<(), StoreError>;
+}
+
+/// Opens (or creates) a RocksDB database with every column `alle` uses.
+pub fn open(path: impl AsRef<Path>) -> Result<Db, StoreError> {
+    fs::create_dir_all(&path).map_err(|e| StoreError::new(e.to_string()))?;
+
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+
+    let columns = Column::ALL.iter().map(|c| c.name());
+    Ok(Db::open_cf(&opts, path, columns)?)
+}
+
+fn handle(db: &Db, column: Column) -> Result<&ColumnFamily, StoreError> {
+    db.cf_handle(column.name())
+        .ok_or_else(|| StoreError::new(format!("column family {} is missing", column.name())))
+}
+
+impl TodoStore for Db {
+    fn get(&self, column: Column, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.get_cf(handle(self, column)?, key)?)
+    }
+
+    fn put(&self, column: Column, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        Ok(self.put_cf(handle(self, column)?, key, value)?)
+    }
+
+    fn delete(&self, column: Column, key: &[u8]) -> Result<(), StoreError> {
+        Ok(self.delete_cf(handle(self, column)?, key)?)
+    }
+
+    fn scan(&self, column: Column) -> Result<Vec<Entry>, StoreError> {
+        let mut entries = Vec::new();
+        for item in self.iterator_cf(handle(self, column)?, IteratorMode::Start) {
+            let (key, value) = item?;
+            entries.push((key.into_vec(), value.into_vec()));
+        }
+
+        Ok(entries)
+    }
+
+    fn scan_prefix(&self, column: Column, prefix: &[u8]) -> Result<Vec<Entry>, StoreError> {
+        let mode = IteratorMode::From(prefix, Direction::Forward);
+
+        let mut entries = Vec::new();
+        for item in self.iterator_cf(handle(self, column)?, mode) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            entries.push((key.into_vec(), value.into_vec()));
+        }
+
+        Ok(entries)
+    }
+
+    fn scan_range(
+        &self,
+        column: Column,
+        from: &[u8],
+        to: Option<&[u8]>,
+    ) -> Result<Vec<Entry>, StoreError> {
+        let mode = IteratorMode::From(from, Direction::Forward);
+
+        let mut entries = Vec::new();
+        for item in self.iterator_cf(handle(self, column)?, mode) {
+            let (key, value) = item?;
+            if to.is_some_and(|to| &*key >= to) {
+                break;
+            }
+            entries.push((key.into_vec(), value.into_vec()));
+        }
+
+        Ok(entries)
+    }
+
+    // Re-reading each key inside the transaction both checks it against the
+    // value the batch was built from and makes the commit fail if another
+    // writer touches it before then.
+    fn write(&self, batch: Batch) -> Result<(), StoreError> {
+        let transaction = self.transaction();
+        for read in batch.reads {
+            let current =
+                transaction.get_for_update_cf(handle(self, read.column)?, &read.key, true)?;
+            if current != read.value {
+                return Err(StoreError::conflict());
+            }
+        }
+        for op in batch.ops {
+            match op {
+                BatchOp::Put(column, key, value) => {
+                    transaction.put_cf(handle(self, column)?, key, value)?
+                }
+                BatchOp::Delete(column, key) => {
+                    transaction.delete_cf(handle(self, column)?, key)?
+                }
+            }
+        }
+
+        Ok(transaction.commit()?)
+    }
+}
+
+type Entries = BTreeMap<Vec<u8>, Vec<u8>>;
+
+#[derive(Default)]
+pub struct MemoryStore {
+    columns: Mutex<BTreeMap<Column, Entries>>,
+}
+
+type Columns<'a> = MutexGuard<'a, BTreeMap<Column, Entries>>;
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn columns(&self) -> Result<Columns<'_>, StoreError> {
+        self.columns
+            .lock()
+            .map_err(|_| StoreError::new("memory store lock poisoned"))
+    }
+}
+
+impl TodoStore for MemoryStore {
+    fn get(&self, column: Column, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self
+            .columns()?
+            .get(&column)
+            .and_then(|entries| entries.get(key).cloned()))
+    }
+
+    fn put(&self, column: Column, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        self.columns()?
+            .entry(column)
+            .or_default()
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, column: Column, key: &[u8]) -> Result<(), StoreError> {
+        if let Some(entries) = self.columns()?.get_mut(&column) {
+            entries.remove(key);
+        }
+        Ok(())
+    }
+
+    fn scan(&self, column: Column) -> Result<Vec<Entry>, StoreError> {
+        Ok(self
+            .columns()?
+            .get(&column)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn scan_prefix(&self, column: Column, prefix: &[u8]) -> Result<Vec<Entry>, StoreError> {
+        Ok(self
+            .columns()?
+            .get(&column)
+            .map(|entries| {
+                entries
+                    .range(prefix.to_vec()..)
+                    .take_while(|(k, _)| k.starts_with(prefix))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn scan_range(
+        &self,
+        column: Column,
+        from: &[u8],
+        to: Option<&[u8]>,
+    ) -> Result<Vec<Entry>, StoreError> {
+        let to = match to {
+            Some(to) if to <= from => return Ok(Vec::new()),
+            Some(to) => Bound::Excluded(to),
+            None => Bound::Unbounded,
+        };
+
+        Ok(self
+            .columns()?
+            .get(&column)
+            .map(|entries| {
+                entries
+                    .range::<[u8], _>((Bound::Included(from), to))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn write(&self, batch: Batch) -> Result<(), StoreError> {
+        let mut columns = self.columns()?;
+        for read in batch.reads {
+            let current = columns
+                .get(&read.column)
+                .and_then(|entries| entries.get(&read.key));
+            if current != read.value.as_ref() {
+                return Err(StoreError::conflict());
+            }
+        }
+        for op in batch.ops {
+            match op {
+                BatchOp::Put(column, key, value) => {
+                    columns.entry(column).or_default().insert(key, value);
+                }
+                BatchOp::Delete(column, key) => {
+                    if let Some(entries) = columns.get_mut(&column) {
+                        entries.remove(&key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,304 +1,821 @@
 use std::{
-    fs,
+    collections::HashSet,
+    fmt, fs,
+    path::Path,
     str::{from_utf8, FromStr},
 };
 
-use chrono::NaiveDate;
-use rocksdb::{IteratorMode, Options, DB};
+use chrono::{DateTime, Local, Utc};
+use rocksdb::{Options, DB};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::{
+    date::{self, Due, DueFilter},
+    error::Error,
+    index,
+    query::{Comparison, Condition, Query},
+    recurrence::Recurrence,
+    store::{Batch, Column, TodoStore},
+};
+
+const NEXT_ID_KEY: &[u8] = b"next_id";
+
+// How many times a change is rebuilt and retried after another process wrote
+// the same todos first.
+const MAX_ATTEMPTS: usize = 10;
+
+/// The layout version written with every stored todo. Records without a
+/// version predate versioning and decode as version 0.
+pub const SCHEMA_VERSION: u32 = 7;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 pub struct Todo {
+    pub version: u32,
+    pub id: u64,
     pub name: String,
     pub status: Status,
-    pub due_date: String,
+    #[serde(deserialize_with = "date::deserialize_due_date")]
+    pub due_date: Option<Due>,
     pub note: String,
     pub tags: Vec<String>,
+    pub priority: Option<Priority>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub recurrence: Option<Recurrence>,
+    pub history: Vec<Completion>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// A completed occurrence of a recurring todo.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub due_date: Option<Due>,
+    pub completed_at: DateTime<Utc>,
+    /// The due date the todo moved on to, which `uncomplete` checks to only
+    /// undo a completion nothing has changed since.
+    #[serde(default)]
+    pub next_due: Option<Due>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum Status {
+    #[default]
     ToDo,
+    InProgress,
+    Blocked,
     Done,
+    Cancelled,
+}
+
+impl Status {
+    pub const ALL: [Status; 5] = [
+        Status::ToDo,
+        Status::InProgress,
+        Status::Blocked,
+        Status::Done,
+        Status::Cancelled,
+    ];
+
+    /// The names the status can be given as on the command line, starting with
+    /// the canonical one. Matching ignores case, spaces, dashes and underscores.
+    pub fn names(self) -> &'static [&'static str] {
+        match self {
+            Status::ToDo => &["todo", "to do", "open"],
+            Status::InProgress => &["in-progress", "in progress", "doing"],
+            Status::Blocked => &["blocked"],
+            Status::Done => &["done", "closed", "complete", "completed"],
+            Status::Cancelled => &["cancelled", "canceled"],
+        }
+    }
+
+    /// Whether no more work is expected on the todo.
+    pub fn is_closed(self) -> bool {
+        matches!(self, Status::Done | Status::Cancelled)
+    }
 }
 
-impl ToString for Status {
-    fn to_string(&self) -> String {
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Status::ToDo => String::from("To Do"),
-            Status::Done => String::from("Done"),
+            Status::ToDo => write!(f, "To Do"),
+            Status::InProgress => write!(f, "In Progress"),
+            Status::Blocked => write!(f, "Blocked"),
+            Status::Done => write!(f, "Done"),
+            Status::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
 
 impl FromStr for Status {
-    type Err = String;
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '_'))
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let input = normalize(s);
+
+        Status::ALL
+            .into_iter()
+            .find(|status| status.names().iter().any(|name| normalize(name) == input))
+            .ok_or_else(|| Error::InvalidStatus(s.to_string()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::Low => write!(f, "Low"),
+            Priority::Medium => write!(f, "Medium"),
+            Priority::High => write!(f, "High"),
+            Priority::Critical => write!(f, "Critical"),
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "ToDo" => Ok(Status::ToDo),
-            "Done" => Ok(Status::Done),
-            _ => Err(format!("{} is not a valid status", s)),
+        match s.to_lowercase().as_str() {
+            "low" | "1" => Ok(Priority::Low),
+            "medium" | "2" => Ok(Priority::Medium),
+            "high" | "3" => Ok(Priority::High),
+            "critical" | "4" => Ok(Priority::Critical),
+            _ => Err(Error::InvalidPriority(s.to_string())),
         }
     }
 }
 
-pub fn add_todo(
-    db: &DB,
-    key: &String,
+pub fn add_todo<S: TodoStore>(
+    db: &S,
+    key: &str,
     note_arg: Option<&String>,
     due_date_arg: Option<&String>,
-) -> Result<(), &'static str> {
-    let res = db.get(key).unwrap();
-    if res.is_some() {
-        return Err("Todo with this name already exists");
-    }
+    priority_arg: Option<&String>,
+    recurrence_arg: Option<&String>,
+) -> Result<Todo, Error> {
+    check_name(key)?;
 
     let mut note = String::from("");
-    if note_arg.is_some() {
-        note = note_arg.unwrap().to_owned();
+    if let Some(note_arg) = note_arg {
+        note = note_arg.to_owned();
     }
 
-    let mut due_date = String::from("");
-    if due_date_arg.is_some() {
-        if NaiveDate::parse_from_str(due_date_arg.unwrap(), "%d-%m-%Y").is_err() {
-            return Err("Invalid date format");
-        }
+    let mut due_date = None;
+    if let Some(due_date_arg) = due_date_arg {
+        due_date = Some(parse_due_date(due_date_arg)?);
+    }
 
-        due_date = due_date_arg.unwrap().to_owned();
+    let mut priority = None;
+    if let Some(priority_arg) = priority_arg {
+        priority = Some(Priority::from_str(priority_arg)?);
     }
 
-    let todo = Todo {
-        name: key.to_string(),
-        status: Status::ToDo,
-        due_date,
-        note,
-        tags: Vec::new(),
-    };
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+    let mut recurrence = None;
+    if let Some(recurrence_arg) = recurrence_arg {
+        recurrence = Some(Recurrence::from_str(recurrence_arg)?);
+    }
 
-    Ok(())
+    let created_at = Some(Utc::now());
+    update(db, |batch| {
+        if batch.read(db, Column::Todos, key.as_bytes())?.is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let mut todo = Todo {
+            name: key.to_string(),
+            due_date,
+            note: note.clone(),
+            priority,
+            recurrence,
+            created_at,
+            ..Default::default()
+        };
+        stage_todo(db, batch, &mut todo)?;
+
+        Ok(todo)
+    })
 }
 
-pub fn get_all_todos(db: &DB, status: Option<&String>, tag: Option<&String>) -> Vec<Todo> {
-    let iter = db.iterator(IteratorMode::Start);
-    let mut todos: Vec<Todo> = Vec::new();
+pub fn get_all_todos<S: TodoStore>(
+    db: &S,
+    status: Option<&String>,
+    tag: Option<&String>,
+    priority: Option<&String>,
+) -> Result<Vec<Todo>, Error> {
+    let mut query = Query::new();
+    if let Some(status) = status {
+        query.push(Condition::Status(Status::from_str(status)?));
+    }
+    if let Some(tag) = tag {
+        query.push(Condition::Tags(vec![tag.to_string()]));
+    }
+    if let Some(priority) = priority {
+        let priority = Priority::from_str(priority)?;
+        query.push(Condition::Priority(Comparison::Equal, priority));
+    }
 
-    for item in iter {
-        let (_, todo) = item.unwrap();
-        let todo = from_utf8(&todo).unwrap();
+    find_todos(db, &query)
+}
 
-        todos.push(serde_json::from_str(todo).unwrap());
+/// Returns every todo that matches `query`.
+pub fn find_todos<S: TodoStore>(db: &S, query: &Query) -> Result<Vec<Todo>, Error> {
+    let now = Local::now();
+    if !index::is_current(db)? {
+        update(db, |batch| {
+            let mut todos = Vec::new();
+            for (key, value) in db.scan(Column::Todos)? {
+                batch.expect(Column::Todos, &key, Some(&value));
+                todos.push(decode_todo(&key, &value)?);
+            }
+            index::rebuild(db, batch, &todos)
+        })?;
     }
 
-    if status.is_some() {
-        let status = Status::from_str(status.unwrap()).unwrap();
-        if status == Status::Done {
-            todos.retain(|t| t.status == Status::Done)
-        } else if status == Status::ToDo {
-            todos.retain(|t| t.status == Status::ToDo)
+    let mut todos: Vec<Todo> = Vec::new();
+    match index::candidates(db, query, now)? {
+        Some(names) => {
+            for name in names {
+                if let Some(value) = db.get(Column::Todos, &name)? {
+                    todos.push(decode_todo(&name, &value)?);
+                }
+            }
         }
+        None => todos = decode_all(db)?,
     }
+    todos.retain(|todo| query.matches(todo, now));
 
-    if tag.is_some() {
-        let tag = tag.unwrap();
-        todos.retain(|t| t.tags.contains(&tag.to_string()));
-    }
+    Ok(todos)
+}
 
-    todos
+pub fn get_todo<S: TodoStore>(db: &S, key: &str) -> Result<Todo, Error> {
+    load_todo(db, &mut Batch::new(), key)
 }
 
-pub fn complete_todo(db: &DB, key: &String) -> Result<(), &'static str> {
-    let res = db.get(key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
-    }
-    let val = String::from_utf8(res.unwrap()).unwrap();
+/// Keeps the todos with a due date that `filter` selects. Done and cancelled
+/// todos are never overdue or due soon, but still count as due on their day.
+pub fn filter_by_due(todos: &mut Vec<Todo>, filter: DueFilter, now: DateTime<Local>) {
+    let condition = Condition::DueIs(filter);
+    todos.retain(|todo| condition.matches(todo, now));
+}
+
+/// Marks a todo as done. A recurring todo instead records the completion in
+/// its history and moves on to its next occurrence.
+pub fn complete_todo<S: TodoStore>(db: &S, key: &str) -> Result<Todo, Error> {
+    modify_todo(db, key, mark_done)
+}
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    todo.status = Status::Done;
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+/// Moves a todo to `status`. Setting a recurring todo to done completes the
+/// current occurrence, as [`complete_todo`] does.
+pub fn set_status<S: TodoStore>(db: &S, key: &str, status: &str) -> Result<Todo, Error> {
+    let status = Status::from_str(status)?;
 
-    Ok(())
+    modify_todo(db, key, |todo| {
+        match status {
+            Status::Done => mark_done(todo)?,
+            _ => todo.status = status,
+        }
+        Ok(())
+    })
 }
 
-pub fn uncomplete_todo(db: &DB, key: &String) -> Result<(), &'static str> {
-    let res = db.get(key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
+fn mark_done(todo: &mut Todo) -> Result<(), Error> {
+    match todo.recurrence {
+        Some(recurrence) => {
+            let now = Local::now();
+            // Without a due date the next occurrence counts from today.
+            let due = todo.due_date.unwrap_or(Due::Date(now.date_naive()));
+            let anchor = series_anchor(todo, due);
+            let next = recurrence
+                .next_from(anchor, due, now)
+                .ok_or(Error::InvalidDate)?;
+
+            todo.history.push(Completion {
+                due_date: todo.due_date,
+                completed_at: now.with_timezone(&Utc),
+                next_due: Some(next),
+            });
+            todo.due_date = Some(next);
+            todo.status = Status::ToDo;
+        }
+        None => todo.status = Status::Done,
     }
-    let val = String::from_utf8(res.unwrap()).unwrap();
-
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    todo.status = Status::ToDo;
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
 
     Ok(())
 }
 
-pub fn add_todo_note(db: &DB, key: &String, note: &String) -> Result<(), &'static str> {
-    let res = db.get(&key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
+// The due date the current series of a recurring todo started on. Going back
+// from `due`, each completion whose rollover led to the due date after it is
+// part of the series, so moving the due date starts a new one.
+fn series_anchor(todo: &Todo, due: Due) -> Due {
+    let mut anchor = due;
+    for completion in todo.history.iter().rev() {
+        match completion.due_date {
+            Some(due_date) if completion.next_due == Some(anchor) => anchor = due_date,
+            _ => break,
+        }
     }
-    let val = String::from_utf8(res.unwrap()).unwrap();
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    if !todo.note.is_empty() {
-        return Err("This todo already has a note");
-    }
-    todo.note = note.to_string();
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+    anchor
+}
 
-    Ok(())
+/// Marks a todo as not done. For a recurring todo that moved on to its next
+/// occurrence this undoes the last completion, bringing back the due date it
+/// had, as long as the due date hasn't changed since.
+pub fn uncomplete_todo<S: TodoStore>(db: &S, key: &str) -> Result<Todo, Error> {
+    modify_todo(db, key, |todo| {
+        if todo.recurrence.is_some() && !todo.status.is_closed() {
+            match todo.history.last() {
+                Some(completion)
+                    if completion.next_due.is_some() && completion.next_due == todo.due_date =>
+                {
+                    todo.due_date = completion.due_date;
+                    todo.history.pop();
+                }
+                _ => return Err(Error::NotCompleted),
+            }
+        }
+        todo.status = Status::ToDo;
+
+        Ok(())
+    })
 }
 
-pub fn edit_todo_note(db: &DB, key: &String, new_note: &String) -> Result<(), &'static str> {
-    let res = db.get(&key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
-    }
-    let val = String::from_utf8(res.unwrap()).unwrap();
+pub fn add_todo_note<S: TodoStore>(db: &S, key: &str, note: &str) -> Result<Todo, Error> {
+    modify_todo(db, key, |todo| {
+        if !todo.note.is_empty() {
+            return Err(Error::NoteAlreadyExists);
+        }
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    todo.note = new_note.to_string();
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+        todo.note = note.to_string();
+        Ok(())
+    })
+}
 
-    Ok(())
+pub fn edit_todo_note<S: TodoStore>(db: &S, key: &str, new_note: &str) -> Result<Todo, Error> {
+    modify_todo(db, key, |todo| {
+        todo.note = new_note.to_string();
+        Ok(())
+    })
 }
 
-pub fn remove_todo_note(db: &DB, key: &String) -> Result<(), &'static str> {
-    let res = db.get(&key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
-    }
-    let val = String::from_utf8(res.unwrap()).unwrap();
+pub fn remove_todo_note<S: TodoStore>(db: &S, key: &str) -> Result<Todo, Error> {
+    modify_todo(db, key, |todo| {
+        todo.note = String::from("");
+        Ok(())
+    })
+}
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    todo.note = String::from("");
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+pub fn add_todo_tag<S: TodoStore>(db: &S, key: &str, tag: &str) -> Result<Todo, Error> {
+    modify_todo(db, key, |todo| {
+        if todo.tags.iter().any(|t| t == tag) {
+            return Err(Error::TagAlreadyExists);
+        }
 
-    Ok(())
+        todo.tags.push(tag.to_string());
+        Ok(())
+    })
 }
 
-pub fn add_todo_tag(db: &DB, key: &String, tag: &String) -> Result<(), &'static str> {
-    let res = db.get(&key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
-    }
-    let val = String::from_utf8(res.unwrap()).unwrap();
+pub fn remove_todo_tag<S: TodoStore>(db: &S, key: &str, tag: &str) -> Result<Todo, Error> {
+    modify_todo(db, key, |todo| {
+        if !todo.tags.iter().any(|t| t == tag) {
+            return Err(Error::TagNotFound);
+        }
+
+        todo.tags.retain(|t| t != tag);
+        Ok(())
+    })
+}
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
+pub fn add_due_date<S: TodoStore>(db: &S, key: &str, date: &str) -> Result<Todo, Error> {
+    let date = parse_due_date(date)?;
 
-    if todo.tags.contains(tag) {
-        return Err("This tag is has already been added to this todo");
-    }
+    modify_todo(db, key, |todo| {
+        todo.due_date = Some(date);
+        Ok(())
+    })
+}
 
-    todo.tags.push(tag.to_string());
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+pub fn change_due_date<S: TodoStore>(db: &S, key: &str, new_date: &str) -> Result<Todo, Error> {
+    let date = parse_due_date(new_date)?;
 
-    Ok(())
+    modify_todo(db, key, |todo| {
+        todo.due_date = Some(date);
+        Ok(())
+    })
 }
 
-pub fn remove_todo_tag(db: &DB, key: &String, tag: &String) -> Result<(), &'static str> {
-    let res = db.get(&key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
+pub fn remove_due_date<S: TodoStore>(db: &S, key: &str) -> Result<Todo, Error> {
+    modify_todo(db, key, |todo| {
+        todo.due_date = None;
+        Ok(())
+    })
+}
+
+pub fn set_priority<S: TodoStore>(db: &S, key: &str, priority: &str) -> Result<Todo, Error> {
+    let priority = match priority.to_lowercase().as_str() {
+        "none" => None,
+        _ => Some(Priority::from_str(priority)?),
+    };
+
+    modify_todo(db, key, |todo| {
+        todo.priority = priority;
+        Ok(())
+    })
+}
+
+pub fn set_recurrence<S: TodoStore>(db: &S, key: &str, recurrence: &str) -> Result<Todo, Error> {
+    let recurrence = match recurrence.to_lowercase().as_str() {
+        "none" => None,
+        _ => Some(Recurrence::from_str(recurrence)?),
+    };
+
+    modify_todo(db, key, |todo| {
+        todo.recurrence = recurrence;
+        Ok(())
+    })
+}
+
+pub fn delete_todo<S: TodoStore>(db: &S, key: &str) -> Result<Todo, Error> {
+    update(db, |batch| {
+        let todo = load_todo(db, batch, key)?;
+
+        batch.delete(Column::Todos, todo.name.as_bytes());
+        if todo.id != 0 {
+            batch.delete(Column::Ids, &todo.id.to_be_bytes());
+        }
+        index::unstage(batch, &todo);
+
+        Ok(todo)
+    })
+}
+
+pub fn rename_todo<S: TodoStore>(db: &S, key: &str, new_name: &str) -> Result<Todo, Error> {
+    check_name(new_name)?;
+
+    update(db, |batch| {
+        let mut todo = load_todo(db, batch, key)?;
+        if todo.name == new_name {
+            return Ok(todo);
+        }
+        if batch
+            .read(db, Column::Todos, new_name.as_bytes())?
+            .is_some()
+        {
+            return Err(Error::AlreadyExists);
+        }
+
+        batch.delete(Column::Todos, todo.name.as_bytes());
+        index::unstage(batch, &todo);
+        todo.name = new_name.to_string();
+        stage_todo(db, batch, &mut todo)?;
+
+        Ok(todo)
+    })
+}
+
+/// Rewrites every todo stored with an older schema version in the current
+/// layout, assigning IDs to those that lack one. Returns how many todos were
+/// upgraded.
+pub fn migrate_db<S: TodoStore>(db: &S) -> Result<usize, Error> {
+    update(db, |batch| {
+        let mut migrated = 0;
+
+        for (key, value) in db.scan(Column::Todos)? {
+            let mut todo = decode_todo(&key, &value)?;
+            if todo.version == SCHEMA_VERSION && todo.id != 0 {
+                continue;
+            }
+
+            batch.expect(Column::Todos, &key, Some(&value));
+            stage_todo(db, batch, &mut todo)?;
+            migrated += 1;
+        }
+
+        Ok(migrated)
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImportMode {
+    /// Imported todos replace existing ones with the same name.
+    #[default]
+    Merge,
+    /// All existing todos are deleted first.
+    Replace,
+    /// Existing todos are kept and imported ones with their names dropped.
+    SkipExisting,
+}
+
+impl ImportMode {
+    pub fn names() -> &'static [&'static str] {
+        &["merge", "replace", "skip-existing"]
     }
-    let val = String::from_utf8(res.unwrap()).unwrap();
+}
+
+impl FromStr for ImportMode {
+    type Err = Error;
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    if !todo.tags.contains(tag) {
-        return Err("This tag does not exist for this todo");
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "merge" => Ok(ImportMode::Merge),
+            "replace" => Ok(ImportMode::Replace),
+            "skip-existing" | "skip" => Ok(ImportMode::SkipExisting),
+            _ => Err(Error::InvalidImportMode(s.to_string())),
+        }
     }
+}
 
-    todo.tags.retain(|t| t != tag);
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+/// The names of the todos [`import_todos`] added, updated or skipped. Updated
+/// and skipped todos are the ones whose names collided with existing todos.
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub skipped: Vec<String>,
+}
 
-    Ok(())
+/// Saves `todos` in a single write. Updated todos keep the ID of the todo they
+/// replace and added ones get a new ID, except that `Replace` keeps the
+/// imported IDs where it can.
+pub fn import_todos<S: TodoStore>(
+    db: &S,
+    todos: &[Todo],
+    mode: ImportMode,
+) -> Result<ImportReport, Error> {
+    import(db, todos, mode, |_, imported| Ok(imported))
 }
 
-pub fn add_due_date(db: &DB, key: &String, date: &String) -> Result<(), &'static str> {
-    let date = NaiveDate::parse_from_str(date, "%d-%m-%Y");
-    if date.is_err() {
-        return Err("Invalid date format");
+/// Imports the items of a checklist, which only say whether a todo is done.
+/// Existing todos keep everything but their status and gain the imported tags.
+/// Checked items complete them and unchecked ones reopen closed ones. A
+/// recurring todo only moves on to its next occurrence once the current one
+/// is due, so importing the same checklist again doesn't complete it again.
+pub fn import_checklist<S: TodoStore>(
+    db: &S,
+    todos: &[Todo],
+    mode: ImportMode,
+) -> Result<ImportReport, Error> {
+    import(db, todos, mode, |mut existing, imported| {
+        match (existing.status.is_closed(), imported.status.is_closed()) {
+            (false, true) if occurrence_is_due(&existing, Local::now()) => {
+                mark_done(&mut existing)?
+            }
+            (true, false) => existing.status = imported.status,
+            _ => {}
+        }
+        for tag in imported.tags {
+            if !existing.tags.contains(&tag) {
+                existing.tags.push(tag);
+            }
+        }
+        Ok(existing)
+    })
+}
+
+// Whether a todo isn't recurring or the occurrence it is on is due by the end
+// of today. One without a due date counts from today, as in `mark_done`.
+fn occurrence_is_due(todo: &Todo, now: DateTime<Local>) -> bool {
+    match (todo.recurrence, todo.due_date) {
+        (Some(_), Some(due)) => due.date() <= now.date_naive(),
+        _ => true,
     }
+}
 
-    let res = db.get(&key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
+// Imports `todos`, using `merge` to combine an existing todo with the imported
+// one of the same name.
+fn import<S, F>(db: &S, todos: &[Todo], mode: ImportMode, merge: F) -> Result<ImportReport, Error>
+where
+    S: TodoStore,
+    F: Fn(Todo, Todo) -> Result<Todo, Error>,
+{
+    if let Some(todo) = todos.iter().find(|todo| check_name(&todo.name).is_err()) {
+        return Err(Error::InvalidImport(format!(
+            "todo {} has no name",
+            todo.id
+        )));
+    }
+    if let Some(todo) = todos.iter().find(|todo| todo.version > SCHEMA_VERSION) {
+        return Err(Error::UnsupportedVersion(todo.version));
     }
 
-    let val = String::from_utf8(res.unwrap()).unwrap();
+    update(db, |batch| {
+        let mut report = ImportReport::default();
+
+        let mut kept_ids = HashSet::new();
+        if mode == ImportMode::Replace {
+            for (key, value) in db.scan(Column::Todos)? {
+                batch.expect(Column::Todos, &key, Some(&value));
+                let existing = decode_todo(&key, &value)?;
+                batch.delete(Column::Todos, &key);
+                if existing.id != 0 {
+                    batch.delete(Column::Ids, &existing.id.to_be_bytes());
+                }
+                index::unstage(batch, &existing);
+            }
+
+            kept_ids = todos
+                .iter()
+                .map(|todo| todo.id)
+                .filter(|id| *id != 0)
+                .collect();
+            if let Some(max) = kept_ids.iter().max() {
+                reserve_ids(db, batch, *max)?;
+            }
+        }
+
+        for todo in todos {
+            let mut todo = todo.clone();
+            let existing = match batch.read(db, Column::Todos, todo.name.as_bytes())? {
+                Some(value) => Some(decode_todo(todo.name.as_bytes(), &value)?),
+                None => None,
+            };
+
+            match existing {
+                Some(_) if mode == ImportMode::SkipExisting => {
+                    report.skipped.push(todo.name);
+                    continue;
+                }
+                Some(existing) => {
+                    let id = existing.id;
+                    todo = merge(existing, todo)?;
+                    todo.id = id;
+                    report.updated.push(todo.name.clone());
+                }
+                None => {
+                    // Each imported ID is only kept for the first todo with it.
+                    if !kept_ids.remove(&todo.id) {
+                        todo.id = 0;
+                    }
+                    todo.created_at.get_or_insert_with(Utc::now);
+                    report.added.push(todo.name.clone());
+                }
+            }
+            stage_todo(db, batch, &mut todo)?;
+        }
+
+        Ok(report)
+    })
+}
+
+pub fn drop_db<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+    let _ = DB::destroy(&Options::default(), &path);
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    todo.due_date = date.unwrap().format("%d-%m-%Y").to_string();
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+    fs::remove_dir_all(path)?;
 
     Ok(())
 }
 
-pub fn change_due_date(db: &DB, key: &String, new_date: &String) -> Result<(), &'static str> {
-    let date = NaiveDate::parse_from_str(new_date, "%d-%m-%Y");
-    if date.is_err() {
-        return Err("Invalid date format");
+// Todos are stored under their name, so one without a name could only be
+// reached by its ID.
+fn check_name(name: &str) -> Result<(), Error> {
+    match name.trim().is_empty() {
+        true => Err(Error::EmptyName),
+        false => Ok(()),
     }
+}
 
-    let res = db.get(&key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
-    }
+fn parse_due_date(date: &str) -> Result<Due, Error> {
+    date::parse_due(date, Local::now())
+}
 
-    let val = String::from_utf8(res.unwrap()).unwrap();
+// Looks a todo up by name first and falls back to its ID, so todos whose
+// names happen to be numbers stay reachable.
+fn load_todo<S: TodoStore>(db: &S, batch: &mut Batch, key: &str) -> Result<Todo, Error> {
+    if let Some(value) = batch.read(db, Column::Todos, key.as_bytes())? {
+        return decode_todo(key.as_bytes(), &value);
+    }
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    todo.due_date = date.unwrap().format("%d-%m-%Y").to_string();
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+    let id: u64 = key
+        .strip_prefix('#')
+        .unwrap_or(key)
+        .parse()
+        .map_err(|_| Error::NotFound)?;
+    let name = batch
+        .read(db, Column::Ids, &id.to_be_bytes())?
+        .ok_or(Error::NotFound)?;
+    let value = batch
+        .read(db, Column::Todos, &name)?
+        .ok_or(Error::NotFound)?;
+
+    decode_todo(&name, &value)
+}
 
-    Ok(())
+fn decode_all<S: TodoStore>(db: &S) -> Result<Vec<Todo>, Error> {
+    db.scan(Column::Todos)?
+        .iter()
+        .map(|(key, value)| decode_todo(key, value))
+        .collect()
 }
 
-pub fn remove_due_date(db: &DB, key: &String) -> Result<(), &'static str> {
-    let res = db.get(&key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
+fn decode_todo(key: &[u8], value: &[u8]) -> Result<Todo, Error> {
+    let corrupt = || Error::Corrupt(String::from_utf8_lossy(key).into_owned());
+    let value = from_utf8(value).map_err(|_| corrupt())?;
+
+    let todo: Todo = serde_json::from_str(value).map_err(|_| corrupt())?;
+    if todo.version > SCHEMA_VERSION {
+        return Err(Error::UnsupportedVersion(todo.version));
     }
 
-    let val = String::from_utf8(res.unwrap()).unwrap();
+    Ok(todo)
+}
 
-    let mut todo: Todo = serde_json::from_str(&val).unwrap();
-    todo.due_date = String::from("");
-    let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(key, serialized).unwrap();
+// Stages a change with `stage` and writes it, starting over from a fresh batch
+// when another writer changed something it read in the meantime.
+fn update<S, T, F>(db: &S, mut stage: F) -> Result<T, Error>
+where
+    S: TodoStore,
+    F: FnMut(&mut Batch) -> Result<T, Error>,
+{
+    for _ in 0..MAX_ATTEMPTS {
+        let mut batch = Batch::new();
+        let value = stage(&mut batch)?;
+        match db.write(batch) {
+            Ok(()) => return Ok(value),
+            Err(e) if e.is_conflict() => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
 
-    Ok(())
+    Err(Error::Conflict)
 }
 
-pub fn delete_todo(db: &DB, key: &String) -> Result<(), &'static str> {
-    let res = db.get(key).unwrap();
-    if res.is_none() {
-        return Err("Todo with this name does not exist");
+// Loads the todo `key` refers to, applies `change` to it and saves it,
+// returning the saved todo.
+fn modify_todo<S, F>(db: &S, key: &str, mut change: F) -> Result<Todo, Error>
+where
+    S: TodoStore,
+    F: FnMut(&mut Todo) -> Result<(), Error>,
+{
+    update(db, |batch| {
+        let mut todo = load_todo(db, batch, key)?;
+        change(&mut todo)?;
+        stage_todo(db, batch, &mut todo)?;
+
+        Ok(todo)
+    })
+}
+
+// Adds the writes that store `todo` under its name to `batch`, assigning an
+// ID first if it does not have one yet, and replaces the index entries of the
+// record it overwrites.
+fn stage_todo<S: TodoStore>(db: &S, batch: &mut Batch, todo: &mut Todo) -> Result<(), Error> {
+    if todo.id == 0 {
+        todo.id = next_id(db, batch)?;
+    }
+    todo.version = SCHEMA_VERSION;
+
+    let name = todo.name.as_bytes();
+    let previous = batch.read(db, Column::Todos, name)?;
+    // A record that no longer decodes has no index entries worth removing.
+    if let Some(previous) = previous.and_then(|value| decode_todo(name, &value).ok()) {
+        index::unstage(batch, &previous);
     }
 
-    db.delete(&key).unwrap();
+    let serialized = serde_json::to_string(todo).map_err(Error::Encode)?;
+    batch.put(Column::Todos, name, serialized.as_bytes());
+    batch.put(Column::Ids, &todo.id.to_be_bytes(), name);
+    index::stage(batch, todo);
 
     Ok(())
 }
 
-pub fn drop_db(path: &str) -> std::io::Result<()> {
-    let _ = DB::destroy(&Options::default(), path);
+fn next_id<S: TodoStore>(db: &S, batch: &mut Batch) -> Result<u64, Error> {
+    let id = stored_next_id(db, batch)?;
+    batch.put(Column::Meta, NEXT_ID_KEY, &(id + 1).to_be_bytes());
 
-    fs::remove_dir_all(path)?;
+    Ok(id)
+}
+
+// Makes sure new IDs are handed out above `max`.
+fn reserve_ids<S: TodoStore>(db: &S, batch: &mut Batch, max: u64) -> Result<(), Error> {
+    if stored_next_id(db, batch)? <= max {
+        batch.put(Column::Meta, NEXT_ID_KEY, &(max + 1).to_be_bytes());
+    }
 
     Ok(())
 }
+
+fn stored_next_id<S: TodoStore>(db: &S, batch: &mut Batch) -> Result<u64, Error> {
+    match batch.read(db, Column::Meta, NEXT_ID_KEY)? {
+        Some(value) => {
+            let bytes = value
+                .try_into()
+                .map_err(|_| Error::Corrupt(String::from("next_id")))?;
+            Ok(u64::from_be_bytes(bytes))
+        }
+        None => Ok(1),
+    }
+}
@@ -0,0 +1,188 @@
+use std::io::{BufRead, Write};
+
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+
+use crate::{
+    date::Due,
+    error::Error,
+    todo::{Priority, Status, Todo},
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// todo.txt priorities run from A to Z; everything below D is low.
+fn priority_letter(priority: Priority) -> char {
+    match priority {
+        Priority::Critical => 'A',
+        Priority::High => 'B',
+        Priority::Medium => 'C',
+        Priority::Low => 'D',
+    }
+}
+
+fn letter_priority(letter: &str) -> Option<Priority> {
+    match letter {
+        "A" => Some(Priority::Critical),
+        "B" => Some(Priority::High),
+        "C" => Some(Priority::Medium),
+        _ if letter.len() == 1 && matches!(letter.as_bytes()[0], b'D'..=b'Z') => {
+            Some(Priority::Low)
+        }
+        _ => None,
+    }
+}
+
+/// Formats a todo as a todo.txt line. Tags become `+project`s, statuses other
+/// than to do and done are kept in a `status:` field and the priority of a
+/// completed todo in a `pri:` field, as todo.txt drops it on completion.
+/// Notes are left out. Name words that would be read back as something else
+/// are escaped with a backslash, and tags with spaces can't be written at all.
+pub fn format_todo(todo: &Todo) -> Result<String, Error> {
+    let mut parts = Vec::new();
+
+    if todo.status.is_closed() {
+        parts.push(String::from("x"));
+    } else if let Some(priority) = todo.priority {
+        parts.push(format!("({})", priority_letter(priority)));
+    }
+    // A lone date after `x` would be read as the completion date, which isn't
+    // kept, so completed todos go without their creation date.
+    if let (false, Some(created_at)) = (todo.status.is_closed(), todo.created_at) {
+        let created = created_at.with_timezone(&Local).date_naive();
+        parts.push(created.format(DATE_FORMAT).to_string());
+    }
+
+    for (i, word) in todo.name.split_whitespace().enumerate() {
+        parts.push(escape_word(word, i == 0));
+    }
+    for tag in &todo.tags {
+        if tag.contains(char::is_whitespace) {
+            return Err(Error::InvalidExport(format!(
+                "the tag \"{}\" of {} has a space, which todo.txt can't hold",
+                tag, todo.name
+            )));
+        }
+        parts.push(format!("+{}", tag));
+    }
+    if let Some(due) = &todo.due_date {
+        parts.push(format!("due:{}", due.date().format(DATE_FORMAT)));
+    }
+    if let (true, Some(priority)) = (todo.status.is_closed(), todo.priority) {
+        parts.push(format!("pri:{}", priority_letter(priority)));
+    }
+    if !matches!(todo.status, Status::ToDo | Status::Done) {
+        parts.push(format!("status:{}", todo.status.names()[0]));
+    }
+
+    Ok(parts.join(" "))
+}
+
+// Escapes a name word that starts with a backslash or would be read as a tag
+// or a field, and a first word that would be read as a completion mark,
+// priority or date.
+fn escape_word(word: &str, first: bool) -> String {
+    let special = word.starts_with('\\')
+        || (word.len() > 1 && word.starts_with(['+', '@']))
+        || matches!(word.split_once(':'), Some(("due" | "pri" | "status", _)))
+        || (first && (word == "x" || parse_priority(word).is_some() || parse_date(word).is_some()));
+
+    match special {
+        true => format!("\\{}", word),
+        false => word.to_string(),
+    }
+}
+
+/// Parses a todo.txt line. `+project`s and `@context`s become tags, other
+/// `key:value` fields stay part of the name and a backslash before a word
+/// keeps it part of the name as it is.
+pub fn parse_line(line: &str) -> Result<Todo, Error> {
+    let mut todo = Todo::default();
+    let mut words = line.split_whitespace().peekable();
+
+    if words.peek() == Some(&"x") {
+        words.next();
+        todo.status = Status::Done;
+        // The completion date, if there is one.
+        words.next_if(|word| parse_date(word).is_some());
+    } else if let Some(priority) = words.peek().and_then(|word| parse_priority(word)) {
+        words.next();
+        todo.priority = Some(priority);
+    }
+    if let Some(created) = words.peek().and_then(|word| parse_date(word)) {
+        words.next();
+        let created = Local
+            .from_local_datetime(&created.and_hms_opt(0, 0, 0).expect("midnight"))
+            .earliest()
+            .ok_or(Error::InvalidDate)?;
+        todo.created_at = Some(created.with_timezone(&Utc));
+    }
+
+    let mut name = Vec::new();
+    for word in words {
+        if let Some(word) = word.strip_prefix('\\') {
+            name.push(word);
+            continue;
+        }
+        if let Some(tag) = word
+            .strip_prefix('+')
+            .or_else(|| word.strip_prefix('@'))
+            .filter(|tag| !tag.is_empty())
+        {
+            if !todo.tags.iter().any(|existing| existing == tag) {
+                todo.tags.push(tag.to_string());
+            }
+            continue;
+        }
+
+        match word.split_once(':') {
+            Some(("due", due)) => {
+                let due = parse_date(due).ok_or(Error::InvalidDate)?;
+                todo.due_date = Some(Due::Date(due));
+            }
+            Some(("pri", letter)) => {
+                let priority = letter_priority(letter)
+                    .ok_or_else(|| Error::InvalidPriority(letter.to_string()))?;
+                todo.priority = Some(priority);
+            }
+            Some(("status", status)) => todo.status = status.parse()?,
+            _ => name.push(word),
+        }
+    }
+    todo.name = name.join(" ");
+
+    Ok(todo)
+}
+
+/// Writes one line per todo.
+pub fn write_todos<W: Write>(mut writer: W, todos: &[Todo]) -> Result<(), Error> {
+    for todo in todos {
+        writeln!(writer, "{}", format_todo(todo)?)?;
+    }
+
+    Ok(())
+}
+
+/// Reads the todos in a todo.txt file, skipping blank lines.
+pub fn read_todos<R: BufRead>(reader: R) -> Result<Vec<Todo>, Error> {
+    let mut todos = Vec::new();
+    for (line, text) in reader.lines().enumerate() {
+        let text = text?;
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        let todo = parse_line(&text)
+            .map_err(|e| Error::InvalidImport(format!("line {}: {}", line + 1, e)))?;
+        todos.push(todo);
+    }
+
+    Ok(todos)
+}
+
+fn parse_priority(word: &str) -> Option<Priority> {
+    letter_priority(word.strip_prefix('(')?.strip_suffix(')')?)
+}
+
+fn parse_date(word: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(word, DATE_FORMAT).ok()
+}
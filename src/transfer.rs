@@ -0,0 +1,201 @@
+use std::{
+    io::{BufReader, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    date::Due,
+    error::Error,
+    ical, markdown,
+    output::{self, OutputFormat, CSV_HEADER},
+    query::Query,
+    recurrence::Recurrence,
+    store::TodoStore,
+    todo::{find_todos, Priority, Status, Todo},
+    todotxt,
+};
+
+/// The file formats todos can be exported to and imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Json,
+    Jsonl,
+    Csv,
+    TodoTxt,
+    ICalendar,
+    Markdown,
+}
+
+impl Format {
+    pub fn names() -> &'static [&'static str] {
+        &["json", "jsonl", "csv", "todotxt", "ical", "markdown"]
+    }
+
+    /// The format a file is in going by its extension, if it has a known one.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "jsonl" | "ndjson" => Ok(Format::Jsonl),
+            "csv" => Ok(Format::Csv),
+            "todotxt" | "todo.txt" | "txt" => Ok(Format::TodoTxt),
+            "ical" | "ics" | "icalendar" => Ok(Format::ICalendar),
+            "markdown" | "md" => Ok(Format::Markdown),
+            _ => Err(Error::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+/// Writes every todo to `writer`, ordered by ID.
+pub fn export_todos<S: TodoStore, W: Write>(
+    db: &S,
+    writer: W,
+    format: Format,
+) -> Result<(), Error> {
+    let mut todos = find_todos(db, &Query::new())?;
+    todos.sort_by_key(|todo| todo.id);
+
+    write_todos(writer, &todos, format)
+}
+
+pub fn write_todos<W: Write>(writer: W, todos: &[Todo], format: Format) -> Result<(), Error> {
+    match format {
+        Format::Json => output::write_todos(writer, todos, OutputFormat::Json),
+        Format::Jsonl => output::write_todos(writer, todos, OutputFormat::Jsonl),
+        Format::Csv => output::write_todos(writer, todos, OutputFormat::Csv),
+        Format::TodoTxt => todotxt::write_todos(writer, todos),
+        Format::ICalendar => ical::write_calendar(writer, todos, Utc::now()),
+        Format::Markdown => markdown::write_checklist(writer, todos, false),
+    }
+}
+
+/// Reads todos written by [`write_todos`]. JSON and JSON Lines are told apart
+/// by their contents, and CSV columns may be in any order as long as there is
+/// a `name`.
+pub fn read_todos<R: Read>(mut reader: R, format: Format) -> Result<Vec<Todo>, Error> {
+    match format {
+        Format::Json | Format::Jsonl => {
+            let mut contents = String::new();
+            reader.read_to_string(&mut contents)?;
+            read_json(&contents)
+        }
+        Format::Csv => read_csv(reader),
+        Format::TodoTxt => todotxt::read_todos(BufReader::new(reader)),
+        Format::ICalendar => ical::read_calendar(reader),
+        Format::Markdown => markdown::read_checklist(BufReader::new(reader)),
+    }
+}
+
+fn read_json(contents: &str) -> Result<Vec<Todo>, Error> {
+    let invalid = |e: serde_json::Error| Error::InvalidImport(e.to_string());
+
+    if contents.trim_start().starts_with('[') {
+        return serde_json::from_str(contents).map_err(invalid);
+    }
+
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(invalid))
+        .collect()
+}
+
+fn read_csv<R: Read>(reader: R) -> Result<Vec<Todo>, Error> {
+    let invalid = |e: csv::Error| Error::InvalidImport(e.to_string());
+
+    let mut csv = csv::Reader::from_reader(reader);
+    let header = csv.headers().map_err(invalid)?.clone();
+    let column = |name: &str| header.iter().position(|column| column == name);
+    if column("name").is_none() {
+        return Err(Error::InvalidImport(String::from(
+            "the CSV has no name column",
+        )));
+    }
+    let columns: Vec<Option<usize>> = CSV_HEADER.iter().map(|name| column(name)).collect();
+
+    let mut todos = Vec::new();
+    for (line, record) in csv.records().enumerate() {
+        let record = record.map_err(invalid)?;
+        // The name and note are kept as they are; other columns are trimmed.
+        let field = |i: usize| {
+            columns[i]
+                .and_then(|column| record.get(column))
+                .map(|value| match CSV_HEADER[i] {
+                    "name" | "note" => value,
+                    _ => value.trim(),
+                })
+                .filter(|value| !value.is_empty())
+        };
+        let todo = csv_todo(field).map_err(|e| {
+            let e = match e {
+                Error::InvalidImport(e) => e,
+                e => e.to_string(),
+            };
+            Error::InvalidImport(format!("row {}: {}", line + 1, e))
+        })?;
+        todos.push(todo);
+    }
+
+    Ok(todos)
+}
+
+// Builds a todo from the fields of a CSV row, in `CSV_HEADER` order.
+fn csv_todo<'a>(field: impl Fn(usize) -> Option<&'a str>) -> Result<Todo, Error> {
+    let mut todo = Todo {
+        name: field(1).unwrap_or_default().to_string(),
+        note: field(6).unwrap_or_default().to_string(),
+        ..Todo::default()
+    };
+
+    if let Some(id) = field(0) {
+        todo.id = id
+            .parse()
+            .map_err(|_| Error::InvalidImport(format!("{} is not a valid ID", id)))?;
+    }
+    if let Some(status) = field(2) {
+        todo.status = Status::from_str(status)?;
+    }
+    todo.priority = field(3).map(Priority::from_str).transpose()?;
+    todo.due_date = field(4).map(Due::from_str).transpose()?;
+    todo.recurrence = field(5).map(Recurrence::from_str).transpose()?;
+    if let Some(tags) = field(7) {
+        todo.tags = csv_tags(tags)?;
+    }
+    if let Some(created_at) = field(8) {
+        let created_at = DateTime::parse_from_rfc3339(created_at).map_err(|_| {
+            Error::InvalidImport(format!("{} is not a valid timestamp", created_at))
+        })?;
+        todo.created_at = Some(created_at.into());
+    }
+
+    Ok(todo)
+}
+
+// Splits the tags column on the commas outside quotes, the way it's written.
+fn csv_tags(tags: &str) -> Result<Vec<String>, Error> {
+    let mut csv = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(tags.as_bytes());
+    let record = match csv.records().next() {
+        Some(record) => record.map_err(|e| Error::InvalidImport(e.to_string()))?,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(record
+        .iter()
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect())
+}
@@ -0,0 +1,60 @@
+use alle::transfer::Format;
+use alle::{cli, ImportMode, OutputFormat, Status};
+
+#[test]
+fn test_list_status_accepts_aliases() {
+    let matches = cli()
+        .try_get_matches_from(["alle", "list", "-s", "Closed"])
+        .unwrap();
+    let (_, list) = matches.subcommand().unwrap();
+
+    let status = list.get_one::<String>("status").unwrap();
+    assert_eq!(Status::Done, status.parse().unwrap());
+}
+
+#[test]
+fn test_status_accepts_serialized_and_underscored_names() {
+    let matches = cli()
+        .try_get_matches_from(["alle", "list", "-s", "InProgress"])
+        .unwrap();
+    let (_, list) = matches.subcommand().unwrap();
+    let status = list.get_one::<String>("status").unwrap();
+    assert_eq!(Status::InProgress, status.parse().unwrap());
+
+    let matches = cli()
+        .try_get_matches_from(["alle", "set-status", "foo", "in_progress"])
+        .unwrap();
+    let (_, set_status) = matches.subcommand().unwrap();
+    let status = set_status.get_one::<String>("STATUS").unwrap();
+    assert_eq!(Status::InProgress, status.parse().unwrap());
+}
+
+#[test]
+fn test_list_status_rejects_unknown_values() {
+    let result = cli().try_get_matches_from(["alle", "list", "-s", "nope"]);
+
+    let error = result.unwrap_err();
+    assert_eq!(clap::error::ErrorKind::ValueValidation, error.kind());
+    assert!(error.to_string().contains("in-progress"));
+}
+
+#[test]
+fn test_formats_accept_aliases() {
+    let matches = cli()
+        .try_get_matches_from(["alle", "list", "-o", "NDJSON"])
+        .unwrap();
+    assert_eq!(
+        Some(&OutputFormat::Jsonl),
+        matches.get_one::<OutputFormat>("output")
+    );
+
+    let matches = cli()
+        .try_get_matches_from(["alle", "import", "todos", "-f", "md", "-m", "skip"])
+        .unwrap();
+    let (_, import) = matches.subcommand().unwrap();
+    assert_eq!(Some(&Format::Markdown), import.get_one::<Format>("format"));
+    assert_eq!(
+        Some(&ImportMode::SkipExisting),
+        import.get_one::<ImportMode>("mode")
+    );
+}
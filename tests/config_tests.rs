@@ -0,0 +1,92 @@
+use alle::config::{parse_config, Config, TableColumn, TableStyle};
+use alle::Error;
+use comfy_table::Color;
+
+#[test]
+fn test_empty_config_uses_defaults() {
+    let config = parse_config("").unwrap();
+    let defaults = Config::default();
+
+    assert_eq!(None, config.database);
+    assert_eq!("%d-%m-%Y", config.dates.output_format);
+    assert_eq!(TableStyle::Rounded, config.table.style);
+    assert_eq!(TableColumn::ALL.to_vec(), config.table.columns);
+    assert_eq!(defaults.colors, config.colors);
+}
+
+#[test]
+fn test_parse_config() {
+    let config = parse_config(
+        r##"
+database = "/srv/todos"
+
+[dates]
+input_format = "%m/%d/%Y"
+output_format = "%Y-%m-%d"
+
+[list]
+query = "!status:done"
+sort = "due"
+
+[table]
+style = "ascii"
+columns = ["name", "due"]
+
+[colors]
+todo = "blue"
+overdue = "#ff0000"
+done = "none"
+"##,
+    )
+    .unwrap();
+
+    assert_eq!(Some("/srv/todos".into()), config.database);
+    assert_eq!("%Y-%m-%d", config.dates.output_format);
+    assert_eq!(Some(String::from("!status:done")), config.list.query);
+    assert_eq!(Some(String::from("due")), config.list.sort);
+    assert_eq!(TableStyle::Ascii, config.table.style);
+    assert_eq!(
+        vec![TableColumn::Name, TableColumn::Due],
+        config.table.columns
+    );
+    assert_eq!(Some(Color::Blue), config.colors.todo);
+    assert_eq!(
+        Some(Color::Rgb { r: 255, g: 0, b: 0 }),
+        config.colors.overdue
+    );
+    assert_eq!(None, config.colors.done);
+    assert_eq!(Some(Color::Yellow), config.colors.due_soon);
+}
+
+#[test]
+fn test_normalize_input_date() {
+    let config = parse_config("[dates]\ninput_format = \"%m/%d/%Y\"").unwrap();
+
+    assert_eq!("2026-12-24", config.dates.normalize("12/24/2026"));
+    assert_eq!("tomorrow", config.dates.normalize("tomorrow"));
+    assert_eq!(
+        "24/12/2026",
+        parse_config("").unwrap().dates.normalize("24/12/2026")
+    );
+}
+
+#[test]
+fn test_invalid_configs() {
+    for contents in [
+        "unknown = 1",
+        "[table]\nstyle = \"fancy\"",
+        "[table]\ncolumns = [\"owner\"]",
+        "[colors]\ntodo = \"plaid\"",
+        "[colors]\nurgent = \"red\"",
+        "[dates]\noutput_format = \"%Q\"",
+        "[dates]\noutput_format = \"%Y-%m-%d %H:%M\"",
+        "[dates]\noutput_format = \"%d.%m.%Y %Z\"",
+    ] {
+        let result = parse_config(contents);
+        assert!(
+            matches!(result, Err(Error::InvalidConfig(_))),
+            "{}",
+            contents
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use alle::date::{parse_date, parse_due, parse_duration, Due, DueFilter};
+use alle::Error;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone};
+
+// A Wednesday.
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, 14).unwrap()
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn now() -> DateTime<Local> {
+    Local
+        .from_local_datetime(&today().and_hms_opt(12, 0, 0).unwrap())
+        .unwrap()
+}
+
+fn local_time(due: Due) -> (NaiveDate, NaiveTime) {
+    let deadline = due.deadline().naive_local();
+    (deadline.date(), deadline.time())
+}
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+#[test]
+fn test_parse_absolute_dates() {
+    assert_eq!(
+        date(2026, 11, 1),
+        parse_date("2026-11-01", today()).unwrap()
+    );
+    assert_eq!(
+        date(2022, 7, 17),
+        parse_date("17-07-2022", today()).unwrap()
+    );
+}
+
+#[test]
+fn test_parse_relative_days() {
+    assert_eq!(today(), parse_date("today", today()).unwrap());
+    assert_eq!(date(2026, 10, 15), parse_date("Tomorrow", today()).unwrap());
+    assert_eq!(
+        date(2026, 10, 13),
+        parse_date("yesterday", today()).unwrap()
+    );
+    assert_eq!(
+        date(2026, 10, 21),
+        parse_date("next week", today()).unwrap()
+    );
+    assert_eq!(
+        date(2026, 11, 14),
+        parse_date("next month", today()).unwrap()
+    );
+}
+
+#[test]
+fn test_parse_offsets() {
+    assert_eq!(date(2026, 10, 17), parse_date("+3d", today()).unwrap());
+    assert_eq!(date(2026, 10, 28), parse_date("+2w", today()).unwrap());
+    assert_eq!(date(2027, 1, 14), parse_date("+3m", today()).unwrap());
+    assert_eq!(date(2027, 10, 14), parse_date("+1y", today()).unwrap());
+}
+
+#[test]
+fn test_parse_weekdays() {
+    assert_eq!(date(2026, 10, 16), parse_date("friday", today()).unwrap());
+    assert_eq!(date(2026, 10, 16), parse_date("next fri", today()).unwrap());
+    assert_eq!(today(), parse_date("wednesday", today()).unwrap());
+    assert_eq!(date(2026, 10, 21), parse_date("next wed", today()).unwrap());
+    assert_eq!(date(2026, 10, 19), parse_date("mon", today()).unwrap());
+}
+
+#[test]
+fn test_parse_invalid_dates() {
+    for input in ["17-07-222022", "someday", "+3x", "+d", "next", "2026-13-01"] {
+        let result = parse_date(input, today());
+        assert!(matches!(result, Err(Error::InvalidDate)), "{}", input);
+    }
+}
+
+#[test]
+fn test_parse_due_without_time() {
+    assert_eq!(
+        Due::Date(date(2026, 10, 15)),
+        parse_due("tomorrow", now()).unwrap()
+    );
+    assert_eq!(
+        Due::Date(date(2026, 10, 17)),
+        parse_due("+3d", now()).unwrap()
+    );
+}
+
+#[test]
+fn test_parse_due_with_local_time() {
+    assert_eq!(
+        (date(2026, 10, 15), time(14, 30)),
+        local_time(parse_due("tomorrow 14:30", now()).unwrap())
+    );
+    assert_eq!(
+        (date(2026, 10, 16), time(18, 15)),
+        local_time(parse_due("friday 6:15pm", now()).unwrap())
+    );
+    assert_eq!(
+        (date(2026, 11, 1), time(9, 0)),
+        local_time(parse_due("2026-11-01T09:00", now()).unwrap())
+    );
+    assert_eq!(
+        (today(), time(0, 0)),
+        local_time(parse_due("12am", now()).unwrap())
+    );
+}
+
+#[test]
+fn test_parse_due_with_time_zone() {
+    let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+    let expected = offset.with_ymd_and_hms(2026, 11, 1, 9, 0, 0).unwrap();
+
+    for input in [
+        "2026-11-01 09:00 +02:00",
+        "2026-11-01 9am +0200",
+        "2026-11-01 07:00 UTC",
+        "2026-11-01T09:00:00+02:00",
+    ] {
+        let due = parse_due(input, now()).unwrap();
+        match due {
+            Due::DateTime(datetime) => assert_eq!(expected, datetime, "{}", input),
+            Due::Date(_) => panic!("{} parsed without a time", input),
+        }
+    }
+}
+
+#[test]
+fn test_parse_due_hour_offset() {
+    let due = parse_due("+3h", now()).unwrap();
+    assert_eq!(now() + Duration::hours(3), due.deadline());
+}
+
+#[test]
+fn test_parse_invalid_due_times() {
+    for input in [
+        "tomorrow 25:00",
+        "tomorrow 13pm",
+        "someday 10:00",
+        "10:00 +25",
+    ] {
+        let result = parse_due(input, now());
+        assert!(matches!(result, Err(Error::InvalidDate)), "{}", input);
+    }
+}
+
+#[test]
+fn test_date_only_due_is_overdue_after_end_of_day() {
+    let due = Due::Date(today());
+    assert!(!due.is_overdue(now()));
+    assert!(due.is_overdue(now() + Duration::days(1)));
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(Duration::days(3), parse_duration("3d").unwrap());
+    assert_eq!(Duration::weeks(2), parse_duration("2W").unwrap());
+    assert_eq!(Duration::hours(12), parse_duration("12h").unwrap());
+
+    for input in ["3", "d", "-1d", "3y"] {
+        let result = parse_duration(input);
+        assert!(
+            matches!(result, Err(Error::InvalidDuration(_))),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_due_filters() {
+    let yesterday = Due::Date(date(2026, 10, 13));
+    let tomorrow = Due::Date(date(2026, 10, 15));
+    let next_week = Due::Date(date(2026, 10, 21));
+    let within = DueFilter::Within(Duration::days(3));
+
+    assert!(DueFilter::Overdue.matches(&yesterday, now()));
+    assert!(!DueFilter::Overdue.matches(&Due::Date(today()), now()));
+    assert!(DueFilter::Today.matches(&Due::Date(today()), now()));
+    assert!(!DueFilter::Today.matches(&tomorrow, now()));
+    assert!(within.matches(&tomorrow, now()));
+    assert!(!within.matches(&next_week, now()));
+    assert!(!within.matches(&yesterday, now()));
+}
+
+#[test]
+fn test_format_falls_back_for_time_formats() {
+    let due = Due::Date(date(2026, 10, 20));
+
+    assert_eq!("2026/10/20", due.format("%Y/%m/%d"));
+    assert_eq!("20-10-2026", due.format("%Y-%m-%d %H:%M"));
+}
@@ -0,0 +1,220 @@
+use alle::date::Due;
+use alle::ical::{read_calendar, write_calendar};
+use alle::recurrence::{Frequency, Recurrence};
+use alle::todo::{Priority, Status, Todo};
+use alle::Error;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap()
+}
+
+fn written(todos: &[Todo]) -> String {
+    let mut out = Vec::new();
+    write_calendar(&mut out, todos, now()).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn todo() -> Todo {
+    Todo {
+        id: 7,
+        name: String::from("Plan trip; book hotels, flights"),
+        note: String::from("Ask about\nthe dates"),
+        status: Status::InProgress,
+        due_date: Some(Due::Date(NaiveDate::from_ymd_opt(2026, 11, 1).unwrap())),
+        priority: Some(Priority::High),
+        tags: vec![String::from("travel"), String::from("a,b")],
+        recurrence: Some(Recurrence::new(Frequency::Weekly, 2)),
+        created_at: Some(Utc.with_ymd_and_hms(2026, 10, 1, 8, 0, 0).unwrap()),
+        ..Todo::default()
+    }
+}
+
+#[test]
+fn test_write_calendar() {
+    let calendar = written(&[todo()]);
+
+    assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(calendar.ends_with("END:VTODO\r\nEND:VCALENDAR\r\n"));
+    for line in [
+        "UID:7-57b0482e4b9c3c31@alle",
+        "DTSTAMP:20261018T093000Z",
+        "SUMMARY:Plan trip\\; book hotels\\, flights",
+        "DESCRIPTION:Ask about\\nthe dates",
+        "DUE;VALUE=DATE:20261101",
+        "STATUS:IN-PROCESS",
+        "PRIORITY:3",
+        "CATEGORIES:travel,a\\,b",
+        "RRULE:FREQ=WEEKLY;INTERVAL=2",
+    ] {
+        assert!(calendar.contains(&format!("\r\n{}\r\n", line)), "{}", line);
+    }
+}
+
+#[test]
+fn test_uids_are_stable_and_unique() {
+    let todos = [
+        Todo {
+            name: String::from("foo"),
+            ..Todo::default()
+        },
+        Todo {
+            name: String::from("bar"),
+            ..Todo::default()
+        },
+    ];
+    let uids = |now: DateTime<Utc>| {
+        let mut out = Vec::new();
+        write_calendar(&mut out, &todos, now).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .filter(|line| line.starts_with("UID:"))
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+
+    let first = uids(now());
+    assert_eq!(2, first.len());
+    assert_ne!(first[0], first[1]);
+    assert_eq!(first, uids(now() + chrono::Duration::hours(1)));
+}
+
+#[test]
+fn test_long_lines_are_folded() {
+    let todo = Todo {
+        name: "é".repeat(100),
+        ..Todo::default()
+    };
+    let calendar = written(std::slice::from_ref(&todo));
+
+    assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+    assert_eq!(
+        todo.name,
+        read_calendar(calendar.as_bytes()).unwrap()[0].name
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let blocked = Todo {
+        name: String::from("Waiting"),
+        status: Status::Blocked,
+        due_date: Some(Due::DateTime(
+            DateTime::parse_from_rfc3339("2026-10-20T14:00:00+02:00").unwrap(),
+        )),
+        ..Todo::default()
+    };
+    let todos = vec![todo(), blocked];
+
+    let read = read_calendar(written(&todos).as_bytes()).unwrap();
+
+    assert_eq!(2, read.len());
+    for (todo, read) in todos.iter().zip(&read) {
+        assert_eq!(todo.name, read.name);
+        assert_eq!(todo.note, read.note);
+        assert_eq!(todo.status, read.status);
+        assert_eq!(todo.priority, read.priority);
+        assert_eq!(todo.tags, read.tags);
+        assert_eq!(todo.recurrence, read.recurrence);
+        assert_eq!(todo.created_at, read.created_at);
+        assert_eq!(
+            todo.due_date.map(|due| due.deadline()),
+            read.due_date.map(|due| due.deadline())
+        );
+    }
+}
+
+#[test]
+fn test_read_calendar_from_other_clients() {
+    let calendar = "BEGIN:VCALENDAR\n\
+        BEGIN:VEVENT\nSUMMARY:Not a todo\nEND:VEVENT\n\
+        BEGIN:VTODO\nSUMMARY:Pay \n rent\nSTATUS:COMPLETED\nPRIORITY:0\n\
+        DUE:20261101T090000\nCATEGORIES:home\nCATEGORIES:bills\n\
+        RRULE:FREQ=MONTHLY;BYDAY=1MO\n\
+        BEGIN:VALARM\nDESCRIPTION:Reminder\nEND:VALARM\nEND:VTODO\n\
+        END:VCALENDAR\n";
+
+    let todos = read_calendar(calendar.as_bytes()).unwrap();
+
+    assert_eq!(1, todos.len());
+    assert_eq!("Pay rent", todos[0].name);
+    assert_eq!("", todos[0].note);
+    assert_eq!(Status::Done, todos[0].status);
+    assert_eq!(None, todos[0].priority);
+    assert_eq!(vec!["home", "bills"], todos[0].tags);
+    assert!(matches!(todos[0].due_date, Some(Due::DateTime(_))));
+    assert_eq!(None, todos[0].recurrence);
+}
+
+#[test]
+fn test_read_invalid_calendar() {
+    for calendar in [
+        "BEGIN:VTODO\nSTATUS:COMPLETED\nEND:VTODO\n",
+        "BEGIN:VTODO\nSUMMARY:a\nSTATUS:LATER\nEND:VTODO\n",
+        "BEGIN:VTODO\nSUMMARY:a\n",
+        "BEGIN:VTODO\nnonsense\nEND:VTODO\n",
+    ] {
+        assert!(
+            matches!(
+                read_calendar(calendar.as_bytes()),
+                Err(Error::InvalidImport(_))
+            ),
+            "{}",
+            calendar
+        );
+    }
+}
+
+#[test]
+fn test_read_times_in_named_zones() {
+    let due = |tzid: &str| {
+        let calendar = format!(
+            "BEGIN:VTODO\nSUMMARY:a\nDUE;TZID={}:20261101T090000\nEND:VTODO\n",
+            tzid
+        );
+        read_calendar(calendar.as_bytes()).unwrap()[0]
+            .due_date
+            .unwrap()
+    };
+
+    assert_eq!(
+        Utc.with_ymd_and_hms(2026, 11, 1, 9, 0, 0).unwrap(),
+        due("UTC").deadline()
+    );
+    if let Ok(local) = iana_time_zone::get_timezone() {
+        assert_eq!(
+            Local.with_ymd_and_hms(2026, 11, 1, 9, 0, 0).unwrap(),
+            due(&format!("\"{}\"", local)).deadline()
+        );
+    }
+    assert_eq!(
+        Due::Date(NaiveDate::from_ymd_opt(2026, 11, 1).unwrap()),
+        due("Mars/Olympus_Mons")
+    );
+}
+
+#[test]
+fn test_read_times_with_vtimezone() {
+    let calendar = "BEGIN:VCALENDAR\n\
+        BEGIN:VTIMEZONE\nTZID:Europe/Sofia\n\
+        BEGIN:DAYLIGHT\nDTSTART:19970330T030000\nTZOFFSETFROM:+0200\nTZOFFSETTO:+0300\n\
+        RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\nEND:DAYLIGHT\n\
+        BEGIN:STANDARD\nDTSTART:19971026T040000\nTZOFFSETFROM:+0300\nTZOFFSETTO:+0200\n\
+        RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\nEND:STANDARD\n\
+        END:VTIMEZONE\n\
+        BEGIN:VTODO\nSUMMARY:summer\nDUE;TZID=Europe/Sofia:20260701T090000\nEND:VTODO\n\
+        BEGIN:VTODO\nSUMMARY:winter\nDUE;TZID=Europe/Sofia:20261101T090000\nEND:VTODO\n\
+        END:VCALENDAR\n";
+
+    let due: Vec<String> = read_calendar(calendar.as_bytes())
+        .unwrap()
+        .iter()
+        .map(|todo| todo.due_date.unwrap().to_string())
+        .collect();
+
+    assert_eq!(
+        vec!["2026-07-01T09:00:00+03:00", "2026-11-01T09:00:00+02:00"],
+        due
+    );
+}
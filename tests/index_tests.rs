@@ -0,0 +1,100 @@
+use alle::query::{Condition, Query};
+use alle::store::{Column, MemoryStore, TodoStore};
+use alle::todo::{
+    add_todo, add_todo_tag, complete_todo, delete_todo, find_todos, remove_todo_tag, rename_todo,
+};
+
+fn index_keys(db: &MemoryStore, column: Column) -> Vec<String> {
+    db.scan(column)
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| String::from_utf8_lossy(&key).replace('\0', "/"))
+        .collect()
+}
+
+fn tag_query(tag: &str) -> Query {
+    let mut query = Query::new();
+    query.push(Condition::Tags(vec![tag.to_string()]));
+    query
+}
+
+fn names(query: &Query, db: &MemoryStore) -> Vec<String> {
+    find_todos(db, query)
+        .unwrap()
+        .into_iter()
+        .map(|todo| todo.name)
+        .collect()
+}
+
+#[test]
+fn test_scan_prefix() {
+    let db = MemoryStore::new();
+    for key in ["work/a", "work/b", "workshop/c", "home/d"] {
+        db.put(Column::ByTag, key.as_bytes(), &[]).unwrap();
+    }
+
+    let keys: Vec<Vec<u8>> = db
+        .scan_prefix(Column::ByTag, b"work/")
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(vec![b"work/a".to_vec(), b"work/b".to_vec()], keys);
+}
+
+#[test]
+fn test_indexes_follow_changes() {
+    let db = MemoryStore::new();
+
+    let due_date = String::from("2099-01-01");
+    add_todo(&db, "foo", None, Some(&due_date), None, None).unwrap();
+    add_todo_tag(&db, "foo", "work").unwrap();
+    add_todo_tag(&db, "foo", "home").unwrap();
+    assert_eq!(vec!["home/foo", "work/foo"], index_keys(&db, Column::ByTag));
+    assert_eq!(vec!["ToDo/foo"], index_keys(&db, Column::ByStatus));
+    assert_eq!(1, index_keys(&db, Column::ByDue).len());
+
+    remove_todo_tag(&db, "foo", "home").unwrap();
+    complete_todo(&db, "foo").unwrap();
+    assert_eq!(vec!["work/foo"], index_keys(&db, Column::ByTag));
+    assert_eq!(vec!["Done/foo"], index_keys(&db, Column::ByStatus));
+
+    rename_todo(&db, "foo", "bar").unwrap();
+    assert_eq!(vec!["work/bar"], index_keys(&db, Column::ByTag));
+    assert_eq!(vec!["Done/bar"], index_keys(&db, Column::ByStatus));
+
+    delete_todo(&db, "bar").unwrap();
+    assert!(index_keys(&db, Column::ByTag).is_empty());
+    assert!(index_keys(&db, Column::ByStatus).is_empty());
+    assert!(index_keys(&db, Column::ByDue).is_empty());
+}
+
+#[test]
+fn test_find_todos_with_indexes() {
+    let db = MemoryStore::new();
+
+    for name in ["foo", "bar", "baz"] {
+        add_todo(&db, name, None, None, None, None).unwrap();
+    }
+    add_todo_tag(&db, "foo", "work").unwrap();
+    add_todo_tag(&db, "baz", "work").unwrap();
+    complete_todo(&db, "baz").unwrap();
+
+    assert_eq!(vec!["baz", "foo"], names(&tag_query("work"), &db));
+
+    let mut query = tag_query("work");
+    query.push(Condition::Status(alle::Status::ToDo));
+    assert_eq!(vec!["foo"], names(&query, &db));
+}
+
+#[test]
+fn test_unindexed_store_is_reindexed() {
+    let db = MemoryStore::new();
+
+    let record = r#"{"name":"foo","tags":["work"]}"#;
+    db.put(Column::Todos, b"foo", record.as_bytes()).unwrap();
+    assert!(index_keys(&db, Column::ByTag).is_empty());
+
+    assert_eq!(vec!["foo"], names(&tag_query("work"), &db));
+    assert_eq!(vec!["work/foo"], index_keys(&db, Column::ByTag));
+}
@@ -0,0 +1,149 @@
+use std::{fs, path::PathBuf};
+
+use alle::location::{find_local_db, resolve_db_path};
+use alle::Error;
+use tempfile::TempDir;
+
+fn data_dir() -> Option<PathBuf> {
+    Some(PathBuf::from("/data"))
+}
+
+#[test]
+fn test_default_db_path() {
+    let path = resolve_db_path(None, None, None, None, None, data_dir(), None).unwrap();
+    assert_eq!(PathBuf::from("/data/alle/default"), path);
+}
+
+#[test]
+fn test_db_path_precedence() {
+    let db = PathBuf::from("/explicit");
+    let env_db = Some(PathBuf::from("/from-env"));
+
+    let path = resolve_db_path(
+        Some(&db),
+        Some("work"),
+        env_db.clone(),
+        None,
+        None,
+        data_dir(),
+        None,
+    );
+    assert_eq!(db, path.unwrap());
+
+    let path = resolve_db_path(
+        None,
+        Some("work"),
+        env_db.clone(),
+        None,
+        None,
+        data_dir(),
+        None,
+    );
+    assert_eq!(PathBuf::from("/data/alle/work"), path.unwrap());
+
+    let path = resolve_db_path(None, None, env_db, None, None, data_dir(), None);
+    assert_eq!(PathBuf::from("/from-env"), path.unwrap());
+}
+
+#[test]
+fn test_invalid_list_names() {
+    for name in ["", ".", "..", "../work", "a/b"] {
+        let result = resolve_db_path(None, Some(name), None, None, None, data_dir(), None);
+        assert!(matches!(result, Err(Error::InvalidListName(_))), "{}", name);
+    }
+}
+
+#[test]
+fn test_db_path_without_data_dir() {
+    let result = resolve_db_path(None, None, None, None, None, None, None);
+    assert!(matches!(result, Err(Error::NoDataDir)));
+
+    let db = PathBuf::from("/explicit");
+    let result = resolve_db_path(Some(&db), None, None, None, None, None, None);
+    assert_eq!(db, result.unwrap());
+}
+
+#[test]
+fn test_legacy_db_is_kept() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path().to_path_buf();
+    fs::create_dir_all(home.join(".alle")).unwrap();
+
+    let path = resolve_db_path(None, None, None, None, None, data_dir(), Some(home.clone()));
+    assert_eq!(home.join(".alle"), path.unwrap());
+
+    let path = resolve_db_path(
+        None,
+        Some("work"),
+        None,
+        None,
+        None,
+        data_dir(),
+        Some(home.clone()),
+    );
+    assert_eq!(PathBuf::from("/data/alle/work"), path.unwrap());
+}
+
+#[test]
+fn test_find_local_db_in_parent() {
+    // The temporary directory stands in for home, so the search stays in it.
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("project");
+    let nested = project.join("src").join("bin");
+    fs::create_dir_all(&nested).unwrap();
+    assert_eq!(None, find_local_db(&nested, Some(home.path())).unwrap());
+
+    fs::create_dir(project.join(".alle")).unwrap();
+    let found = find_local_db(&nested, Some(home.path())).unwrap();
+    assert_eq!(Some(project.join(".alle")), found);
+
+    let path = resolve_db_path(None, None, None, Some(&nested), None, data_dir(), None);
+    assert_eq!(project.join(".alle"), path.unwrap());
+
+    let path = resolve_db_path(
+        None,
+        Some("work"),
+        None,
+        Some(&nested),
+        None,
+        data_dir(),
+        None,
+    );
+    assert_eq!(PathBuf::from("/data/alle/work"), path.unwrap());
+
+    let configured = PathBuf::from("/configured");
+    let path = resolve_db_path(None, None, None, None, Some(&configured), data_dir(), None);
+    assert_eq!(configured, path.unwrap());
+
+    let path = resolve_db_path(
+        None,
+        None,
+        None,
+        Some(&nested),
+        Some(&configured),
+        data_dir(),
+        None,
+    );
+    assert_eq!(project.join(".alle"), path.unwrap());
+}
+
+#[test]
+fn test_find_local_db_from_file() {
+    let temp = TempDir::new().unwrap();
+    let project = temp.path();
+    fs::write(project.join(".alle"), "../shared-todos\n").unwrap();
+
+    let found = find_local_db(project, None).unwrap();
+    assert_eq!(Some(project.join("../shared-todos")), found);
+}
+
+#[test]
+fn test_find_local_db_skips_global_db() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    fs::create_dir_all(home.join(".alle")).unwrap();
+    let project = home.join("project");
+    fs::create_dir_all(&project).unwrap();
+
+    assert_eq!(None, find_local_db(&project, Some(home)).unwrap());
+}
@@ -0,0 +1,144 @@
+use alle::date::Due;
+use alle::markdown::{read_checklist, write_checklist, TAG_MARKER};
+use alle::store::MemoryStore;
+use alle::todo::{
+    add_todo, add_todo_tag, complete_todo, get_todo, import_checklist, set_status, ImportMode,
+    Status, Todo,
+};
+use alle::Error;
+use chrono::{Duration, Local};
+
+fn todo(name: &str, status: Status, tags: &[&str]) -> Todo {
+    Todo {
+        name: name.to_string(),
+        status,
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..Todo::default()
+    }
+}
+
+fn written(todos: &[Todo], group_by_tag: bool) -> String {
+    let mut out = Vec::new();
+    write_checklist(&mut out, todos, group_by_tag).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_write_checklist() {
+    let todos = vec![
+        todo("foo", Status::ToDo, &["work"]),
+        todo("bar", Status::Done, &[]),
+        todo("baz", Status::Cancelled, &["home", "work"]),
+    ];
+
+    assert_eq!("- [ ] foo\n- [x] bar\n- [x] baz\n", written(&todos, false));
+    assert_eq!(
+        "<!-- alle: group-by-tag -->\n\n- [x] bar\n\n## work\n\n- [ ] foo\n- [x] baz\n\n\
+         ## home\n\n- [x] baz\n",
+        written(&todos, true)
+    );
+}
+
+#[test]
+fn test_read_checklist() {
+    let checklist = "<!-- alle: group-by-tag -->\n\
+        # Stand-up\n\
+        Some notes\n\
+        - [x] foo\n\
+        * [ ]   bar  \n\
+        - not an item\n\
+        - [?] nor this\n\
+        ## work\n\
+        \x20 + [X] baz\n\
+        - [ ] bar\n\
+        ##\n\
+        - [ ] qux\n";
+
+    let todos = read_checklist(checklist.as_bytes()).unwrap();
+
+    let expected = [
+        todo("foo", Status::Done, &["Stand-up"]),
+        todo("bar", Status::ToDo, &["Stand-up", "work"]),
+        todo("baz", Status::Done, &["work"]),
+        todo("qux", Status::ToDo, &[]),
+    ];
+    assert_eq!(expected.len(), todos.len());
+    for (expected, todo) in expected.iter().zip(&todos) {
+        assert_eq!(expected.name, todo.name);
+        assert_eq!(expected.status, todo.status);
+        assert_eq!(expected.tags, todo.tags);
+    }
+    let untagged = read_checklist(checklist.replace(TAG_MARKER, "").as_bytes()).unwrap();
+    assert!(untagged.iter().all(|todo| todo.tags.is_empty()));
+    assert!(matches!(
+        read_checklist("- [ ]  \n".as_bytes()),
+        Err(Error::InvalidImport(_))
+    ));
+}
+
+#[test]
+fn test_round_trip() {
+    let todos = vec![
+        todo("bar", Status::Done, &[]),
+        todo("foo", Status::ToDo, &["work"]),
+        todo("baz", Status::ToDo, &["work", "home"]),
+    ];
+
+    let read = read_checklist(written(&todos, true).as_bytes()).unwrap();
+
+    assert_eq!(todos.len(), read.len());
+    for (todo, read) in todos.iter().zip(&read) {
+        assert_eq!(todo.name, read.name);
+        assert_eq!(todo.status, read.status);
+        assert_eq!(todo.tags, read.tags);
+    }
+}
+
+#[test]
+fn test_import_checklist() {
+    let db = MemoryStore::new();
+    let note = String::from("keep me");
+    add_todo(&db, "foo", Some(&note), None, None, None).unwrap();
+    add_todo(&db, "bar", None, None, None, None).unwrap();
+    add_todo(&db, "baz", None, None, None, None).unwrap();
+    complete_todo(&db, "bar").unwrap();
+    set_status(&db, "baz", "in-progress").unwrap();
+    add_todo_tag(&db, "foo", "work").unwrap();
+
+    let checklist = format!(
+        "- [x] foo\n- [ ] bar\n- [ ] baz\n{}\n## home\n- [ ] qux\n",
+        TAG_MARKER
+    );
+    let todos = read_checklist(checklist.as_bytes()).unwrap();
+    let report = import_checklist(&db, &todos, ImportMode::Merge).unwrap();
+
+    assert_eq!(vec!["qux"], report.added);
+    assert_eq!(vec!["foo", "bar", "baz"], report.updated);
+    let foo = get_todo(&db, "foo").unwrap();
+    assert_eq!(Status::Done, foo.status);
+    assert_eq!("keep me", foo.note);
+    assert_eq!(vec!["work"], foo.tags);
+    assert_eq!(Status::ToDo, get_todo(&db, "bar").unwrap().status);
+    assert_eq!(Status::InProgress, get_todo(&db, "baz").unwrap().status);
+    let qux = get_todo(&db, "qux").unwrap();
+    assert_eq!((4, vec![String::from("home")]), (qux.id, qux.tags));
+    assert!(qux.created_at.is_some());
+}
+
+#[test]
+fn test_import_checklist_completes_recurring_todos() {
+    let db = MemoryStore::new();
+    let today = Local::now().date_naive();
+    let due = today.to_string();
+    let repeat = String::from("weekly");
+    add_todo(&db, "foo", None, Some(&due), None, Some(&repeat)).unwrap();
+
+    let todos = read_checklist("- [x] foo\n".as_bytes()).unwrap();
+    import_checklist(&db, &todos, ImportMode::Merge).unwrap();
+    import_checklist(&db, &todos, ImportMode::Merge).unwrap();
+
+    let foo = get_todo(&db, "foo").unwrap();
+    assert_eq!(Status::ToDo, foo.status);
+    assert_eq!(Some(Due::Date(today + Duration::weeks(1))), foo.due_date);
+    assert_eq!(1, foo.history.len());
+}
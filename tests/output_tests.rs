@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+use alle::output::{
+    write_import_report, write_migrated, write_todo, write_todos, OutputFormat, CSV_HEADER,
+};
+use alle::store::MemoryStore;
+use alle::todo::{add_todo, add_todo_tag, complete_todo, delete_todo, ImportReport, Todo};
+use alle::Error;
+
+fn todos() -> Vec<Todo> {
+    let db = MemoryStore::new();
+    let due = String::from("2030-01-02");
+    let priority = String::from("high");
+    add_todo(&db, "foo", None, Some(&due), Some(&priority), None).unwrap();
+    add_todo(&db, "bar", None, None, None, None).unwrap();
+
+    vec![
+        add_todo_tag(&db, "foo", "a,b").unwrap(),
+        complete_todo(&db, "bar").unwrap(),
+    ]
+}
+
+fn written(todos: &[Todo], format: OutputFormat) -> String {
+    let mut out = Vec::new();
+    write_todos(&mut out, todos, format).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_parse_output_format() {
+    assert_eq!(OutputFormat::Json, OutputFormat::from_str("JSON").unwrap());
+    assert_eq!(
+        OutputFormat::Jsonl,
+        OutputFormat::from_str("jsonl").unwrap()
+    );
+    assert_eq!(OutputFormat::Csv, OutputFormat::from_str("csv").unwrap());
+    assert_eq!(
+        OutputFormat::Table,
+        OutputFormat::from_str("table").unwrap()
+    );
+    assert!(matches!(
+        OutputFormat::from_str("xml"),
+        Err(Error::InvalidFormat(_))
+    ));
+}
+
+#[test]
+fn test_write_json() {
+    let todos = todos();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&written(&todos, OutputFormat::Json)).unwrap();
+    let records = json.as_array().unwrap();
+    assert_eq!(2, records.len());
+    assert_eq!("foo", records[0]["name"]);
+    assert_eq!("2030-01-02", records[0]["due_date"]);
+    assert_eq!("High", records[0]["priority"]);
+    assert_eq!("Done", records[1]["status"]);
+
+    let mut out = Vec::new();
+    write_todo(&mut out, &todos[0], OutputFormat::Json).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!("foo", json["name"]);
+}
+
+#[test]
+fn test_write_jsonl() {
+    let out = written(&todos(), OutputFormat::Jsonl);
+
+    let names: Vec<String> = out
+        .lines()
+        .map(|line| serde_json::from_str::<Todo>(line).unwrap().name)
+        .collect();
+    assert_eq!(vec!["foo", "bar"], names);
+}
+
+#[test]
+fn test_write_csv() {
+    let out = written(&todos(), OutputFormat::Csv);
+    let lines: Vec<&str> = out.lines().collect();
+
+    assert_eq!(CSV_HEADER.join(","), lines[0]);
+    assert!(lines[1].starts_with("1,foo,ToDo,High,2030-01-02,,,\"\"\"a,b\"\"\","));
+    assert!(lines[2].starts_with("2,bar,Done,,,,,,"));
+}
+
+#[test]
+fn test_changes_return_the_saved_todo() {
+    let db = MemoryStore::new();
+
+    let todo = add_todo(&db, "foo", None, None, None, None).unwrap();
+    assert_eq!(1, todo.id);
+    assert_eq!("ToDo", serde_json::to_value(todo.status).unwrap());
+
+    let todo = add_todo_tag(&db, "foo", "work").unwrap();
+    assert_eq!(vec!["work"], todo.tags);
+
+    let todo = delete_todo(&db, "foo").unwrap();
+    assert_eq!("foo", todo.name);
+}
+
+#[test]
+fn test_write_import_report() {
+    let report = ImportReport {
+        added: vec![String::from("foo")],
+        updated: vec![String::from("bar")],
+        skipped: vec![],
+    };
+    let written = |format| {
+        let mut out = Vec::new();
+        write_import_report(&mut out, &report, format).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    let json: serde_json::Value = serde_json::from_str(&written(OutputFormat::Json)).unwrap();
+    assert_eq!(serde_json::json!(["bar"]), json["updated"]);
+    assert_eq!(
+        "{\"name\":\"foo\",\"result\":\"added\"}\n{\"name\":\"bar\",\"result\":\"updated\"}\n",
+        written(OutputFormat::Jsonl)
+    );
+    assert_eq!(
+        "name,result\nfoo,added\nbar,updated\n",
+        written(OutputFormat::Csv)
+    );
+    assert_eq!("", written(OutputFormat::Table));
+}
+
+#[test]
+fn test_write_migrated() {
+    let mut out = Vec::new();
+    write_migrated(&mut out, 3, OutputFormat::Json).unwrap();
+    assert_eq!("{\"migrated\":3}\n", String::from_utf8(out).unwrap());
+}
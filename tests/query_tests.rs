@@ -0,0 +1,175 @@
+use alle::config::Dates;
+use alle::date::{Due, DueFilter};
+use alle::query::{parse_query, Comparison, Condition, Query};
+use alle::todo::{Priority, Status, Todo};
+use alle::Error;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+
+// A Wednesday.
+fn now() -> DateTime<Local> {
+    Local
+        .from_local_datetime(&date(2026, 10, 14).and_hms_opt(12, 0, 0).unwrap())
+        .unwrap()
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn todo(name: &str, tags: &[&str], priority: Option<Priority>, due: Option<NaiveDate>) -> Todo {
+    Todo {
+        name: name.to_string(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        priority,
+        due_date: due.map(Due::Date),
+        ..Default::default()
+    }
+}
+
+fn selected<'a>(query: &Query, todos: &'a [Todo]) -> Vec<&'a str> {
+    todos
+        .iter()
+        .filter(|todo| query.matches(todo, now()))
+        .map(|todo| todo.name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_parse_query() {
+    let query = parse_query(
+        "status:todo tag:work due<2026-11-01 priority>=high",
+        now(),
+        &Dates::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            Condition::Status(Status::ToDo),
+            Condition::Tags(vec![String::from("work")]),
+            Condition::Due(Comparison::Less, Due::Date(date(2026, 11, 1))),
+            Condition::Priority(Comparison::GreaterOrEqual, Priority::High),
+        ],
+        query.conditions
+    );
+}
+
+#[test]
+fn test_parse_query_negation_and_alternatives() {
+    let query = parse_query(
+        "!tag:waiting tag:work|home status!=done",
+        now(),
+        &Dates::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            Condition::Not(Box::new(Condition::Tags(vec![String::from("waiting")]))),
+            Condition::Tags(vec![String::from("work"), String::from("home")]),
+            Condition::Not(Box::new(Condition::Status(Status::Done))),
+        ],
+        query.conditions
+    );
+}
+
+#[test]
+fn test_parse_query_relative_dates_and_keywords() {
+    let query = parse_query(
+        "due<=+1w due:overdue priority:none name:\"weekly report\"",
+        now(),
+        &Dates::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            Condition::Due(Comparison::LessOrEqual, Due::Date(date(2026, 10, 21))),
+            Condition::DueIs(DueFilter::Overdue),
+            Condition::NoPriority,
+            Condition::Name(String::from("weekly report")),
+        ],
+        query.conditions
+    );
+}
+
+#[test]
+fn test_parse_query_with_date_input_format() {
+    let dates = Dates {
+        input_format: Some(String::from("%m/%d/%Y")),
+        ..Dates::default()
+    };
+
+    let query = parse_query("due<11/01/2026 !due:10/20/2026", now(), &dates).unwrap();
+
+    assert_eq!(
+        vec![
+            Condition::Due(Comparison::Less, Due::Date(date(2026, 11, 1))),
+            Condition::Not(Box::new(Condition::Due(
+                Comparison::Equal,
+                Due::Date(date(2026, 10, 20))
+            ))),
+        ],
+        query.conditions
+    );
+}
+
+#[test]
+fn test_parse_invalid_queries() {
+    for input in ["status", "size:big", "status<todo", "tag:", "name:\"open"] {
+        let result = parse_query(input, now(), &Dates::default());
+        assert!(matches!(result, Err(Error::InvalidQuery(_))), "{}", input);
+    }
+
+    let result = parse_query("status:someday", now(), &Dates::default());
+    assert!(matches!(result, Err(Error::InvalidStatus(_))));
+}
+
+#[test]
+fn test_query_matches_todos() {
+    let todos = vec![
+        todo(
+            "foo",
+            &["work"],
+            Some(Priority::Critical),
+            Some(date(2026, 10, 20)),
+        ),
+        todo("bar", &["work", "waiting"], Some(Priority::High), None),
+        todo(
+            "baz",
+            &["home"],
+            Some(Priority::Low),
+            Some(date(2026, 12, 1)),
+        ),
+        todo("qux", &[], None, Some(date(2026, 10, 1))),
+    ];
+
+    let query = parse_query("tag:work !tag:waiting", now(), &Dates::default()).unwrap();
+    assert_eq!(vec!["foo"], selected(&query, &todos));
+
+    let query = parse_query("tag:work|home priority>=high", now(), &Dates::default()).unwrap();
+    assert_eq!(vec!["foo", "bar"], selected(&query, &todos));
+
+    let query = parse_query("due<2026-11-01", now(), &Dates::default()).unwrap();
+    assert_eq!(vec!["foo", "qux"], selected(&query, &todos));
+
+    let query = parse_query("due:overdue", now(), &Dates::default()).unwrap();
+    assert_eq!(vec!["qux"], selected(&query, &todos));
+}
+
+#[test]
+fn test_push_tags() {
+    let todos = vec![
+        todo("foo", &["work"], None, None),
+        todo("bar", &["work", "home"], None, None),
+        todo("baz", &["home", "waiting"], None, None),
+    ];
+
+    let mut all = Query::new();
+    all.push_tags(&["work", "home"], false);
+    assert_eq!(vec!["bar"], selected(&all, &todos));
+
+    let mut any = Query::new();
+    any.push_tags(&["work", "home", "!waiting"], true);
+    assert_eq!(vec!["foo", "bar"], selected(&any, &todos));
+}
@@ -0,0 +1,25 @@
+// Sets the process's time zone, so it runs apart from the other tests.
+
+use alle::date::Due;
+use alle::recurrence::{Frequency, Recurrence};
+use chrono::{DateTime, Local, TimeZone};
+
+#[test]
+fn test_next_occurrence_keeps_local_time_across_dst() {
+    // Central European Time, which leaves summer time on 25 October 2026.
+    std::env::set_var("TZ", "CET-1CEST,M3.5.0,M10.5.0/3");
+    let now = Local.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+    let weekly = Recurrence::new(Frequency::Weekly, 1);
+
+    let summer = Due::DateTime(DateTime::parse_from_rfc3339("2026-10-23T09:00:00+02:00").unwrap());
+    let winter = weekly.next(summer, now).unwrap();
+    assert_eq!("2026-10-30T09:00:00+01:00", winter.to_string());
+
+    // A time given with some other offset keeps it.
+    let elsewhere =
+        Due::DateTime(DateTime::parse_from_rfc3339("2026-10-23T09:00:00+05:00").unwrap());
+    assert_eq!(
+        "2026-10-30T09:00:00+05:00",
+        weekly.next(elsewhere, now).unwrap().to_string()
+    );
+}
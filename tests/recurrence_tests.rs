@@ -0,0 +1,132 @@
+use alle::date::Due;
+use alle::recurrence::{Frequency, Recurrence};
+use alle::Error;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone};
+
+fn now() -> DateTime<Local> {
+    Local
+        .from_local_datetime(&date(2026, 10, 14).and_hms_opt(12, 0, 0).unwrap())
+        .unwrap()
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn test_parse_recurrence() {
+    let cases = [
+        ("daily", Recurrence::new(Frequency::Daily, 1)),
+        ("Weekly", Recurrence::new(Frequency::Weekly, 1)),
+        ("every month", Recurrence::new(Frequency::Monthly, 1)),
+        ("every 3 days", Recurrence::new(Frequency::Daily, 3)),
+        ("every 1 year", Recurrence::new(Frequency::Yearly, 1)),
+        (
+            "FREQ=WEEKLY;INTERVAL=2",
+            Recurrence::new(Frequency::Weekly, 2),
+        ),
+        ("RRULE:FREQ=MONTHLY", Recurrence::new(Frequency::Monthly, 1)),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(expected, input.parse().unwrap(), "{}", input);
+    }
+}
+
+#[test]
+fn test_parse_invalid_recurrence() {
+    for input in [
+        "sometimes",
+        "every 0 days",
+        "every x weeks",
+        "FREQ=HOURLY",
+        "FREQ=DAILY;COUNT=3",
+    ] {
+        let result = input.parse::<Recurrence>();
+        assert!(
+            matches!(result, Err(Error::InvalidRecurrence(_))),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_recurrence_round_trips_through_display() {
+    for input in ["daily", "every 2 weeks", "monthly", "every 3 years"] {
+        let recurrence: Recurrence = input.parse().unwrap();
+        assert_eq!(input, recurrence.to_string());
+    }
+}
+
+#[test]
+fn test_next_occurrence() {
+    let due = Due::Date(date(2026, 10, 20));
+
+    let weekly = Recurrence::new(Frequency::Weekly, 1);
+    assert_eq!(Some(Due::Date(date(2026, 10, 27))), weekly.next(due, now()));
+
+    let monthly = Recurrence::new(Frequency::Monthly, 1);
+    assert_eq!(
+        Some(Due::Date(date(2026, 2, 28))),
+        monthly.next(Due::Date(date(2026, 1, 31)), date_now(2026, 1, 31))
+    );
+}
+
+#[test]
+fn test_next_occurrence_keeps_day_of_month() {
+    let monthly = Recurrence::new(Frequency::Monthly, 1);
+    let anchor = Due::Date(date(2026, 1, 31));
+
+    let february = monthly
+        .next_from(anchor, anchor, date_now(2026, 1, 31))
+        .unwrap();
+    assert_eq!(Due::Date(date(2026, 2, 28)), february);
+    let march = monthly
+        .next_from(anchor, february, date_now(2026, 2, 28))
+        .unwrap();
+    assert_eq!(Due::Date(date(2026, 3, 31)), march);
+
+    let yearly = Recurrence::new(Frequency::Yearly, 1);
+    let leap_day = Due::Date(date(2024, 2, 29));
+    let next = yearly
+        .next_from(
+            leap_day,
+            Due::Date(date(2027, 2, 28)),
+            date_now(2027, 2, 28),
+        )
+        .unwrap();
+    assert_eq!(Due::Date(date(2028, 2, 29)), next);
+
+    // A due date that isn't one of the anchor's occurrences starts over.
+    let moved = Due::Date(date(2026, 3, 15));
+    assert_eq!(
+        Some(Due::Date(date(2026, 4, 15))),
+        monthly.next_from(anchor, moved, date_now(2026, 3, 15))
+    );
+}
+
+#[test]
+fn test_next_occurrence_skips_missed_periods() {
+    let daily = Recurrence::new(Frequency::Daily, 1);
+    let due = Due::Date(date(2026, 10, 1));
+
+    assert_eq!(Some(Due::Date(date(2026, 10, 14))), daily.next(due, now()));
+}
+
+#[test]
+fn test_next_occurrence_keeps_time_of_day() {
+    let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+    let due = Due::DateTime(offset.with_ymd_and_hms(2026, 10, 20, 9, 0, 0).unwrap());
+
+    let next = Recurrence::new(Frequency::Daily, 2)
+        .next(due, now())
+        .unwrap();
+    assert_eq!("2026-10-22T09:00:00+02:00", next.to_string());
+}
+
+fn date_now(year: i32, month: u32, day: u32) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&date(year, month, day).and_hms_opt(0, 0, 0).unwrap())
+        .unwrap()
+}
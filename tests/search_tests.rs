@@ -0,0 +1,56 @@
+use alle::search::{search_todos, Matcher, SearchMode};
+use alle::store::MemoryStore;
+use alle::todo::{add_todo, add_todo_tag};
+use alle::Error;
+
+#[test]
+fn test_substring_matches_ignore_case() {
+    let matcher = Matcher::new("rep", SearchMode::Substring).unwrap();
+
+    assert_eq!(vec![0..3, 7..10], matcher.find("Report repeat"));
+    assert!(matcher.find("weekly sync").is_empty());
+}
+
+#[test]
+fn test_substring_matches_non_ascii_text() {
+    let matcher = Matcher::new("ÄPF", SearchMode::Substring).unwrap();
+
+    assert_eq!(vec![0..4], matcher.find("äpfel"));
+}
+
+#[test]
+fn test_regex_matches() {
+    let matcher = Matcher::new(r"q[1-4]", SearchMode::Regex).unwrap();
+    assert_eq!(vec![7..9], matcher.find("Report Q3"));
+
+    let result = Matcher::new("(", SearchMode::Regex);
+    assert!(matches!(result, Err(Error::InvalidPattern(_))));
+}
+
+#[test]
+fn test_fuzzy_matches_letters_in_order() {
+    let matcher = Matcher::new("wkrp", SearchMode::Fuzzy).unwrap();
+
+    assert_eq!(vec![0..1, 3..4, 7..8, 9..10], matcher.find("weekly report"));
+    assert!(matcher.find("report weekly").is_empty());
+}
+
+#[test]
+fn test_search_todos_across_fields() {
+    let db = MemoryStore::new();
+
+    let note = String::from("Ask about the budget");
+    add_todo(&db, "foo", Some(&note), None, None, None).unwrap();
+    add_todo(&db, "budget review", None, None, None, None).unwrap();
+    add_todo(&db, "bar", None, None, None, None).unwrap();
+    add_todo_tag(&db, "bar", "budgeting").unwrap();
+    add_todo(&db, "baz", None, None, None, None).unwrap();
+
+    let hits = search_todos(&db, "BUDGET", SearchMode::Substring).unwrap();
+    let names: Vec<&str> = hits.iter().map(|hit| hit.todo.name.as_str()).collect();
+    assert_eq!(vec!["bar", "budget review", "foo"], names);
+
+    assert_eq!(vec![vec![0..6]], hits[0].tags);
+    assert_eq!(vec![0..6], hits[1].name);
+    assert_eq!(vec![14..20], hits[2].note);
+}
@@ -0,0 +1,110 @@
+use alle::date::Due;
+use alle::sort::{parse_sort, sort_todos, SortField, SortKey};
+use alle::todo::{Priority, Status, Todo};
+use alle::Error;
+use chrono::NaiveDate;
+
+#[test]
+fn test_parse_sort() {
+    let keys = parse_sort("priority:desc, due,Name:ASC").unwrap();
+    assert_eq!(
+        vec![
+            SortKey {
+                field: SortField::Priority,
+                descending: true
+            },
+            SortKey {
+                field: SortField::Due,
+                descending: false
+            },
+            SortKey {
+                field: SortField::Name,
+                descending: false
+            },
+        ],
+        keys
+    );
+}
+
+#[test]
+fn test_parse_sort_with_invalid_field() {
+    let result = parse_sort("due,size");
+    assert!(matches!(result, Err(Error::InvalidSort(_))));
+}
+
+#[test]
+fn test_parse_sort_with_invalid_direction() {
+    let result = parse_sort("due:up");
+    assert!(matches!(result, Err(Error::InvalidSort(_))));
+}
+
+#[test]
+fn test_sort_by_due_date() {
+    let mut todos = vec![
+        todo("foo", Status::ToDo, None, None),
+        todo("bar", Status::ToDo, date(2023, 2, 1), None),
+        todo("baz", Status::ToDo, date(2023, 1, 15), None),
+    ];
+
+    sort_todos(&mut todos, &parse_sort("due").unwrap());
+    assert_eq!(vec!["baz", "bar", "foo"], names(&todos));
+
+    sort_todos(&mut todos, &parse_sort("due:desc").unwrap());
+    assert_eq!(vec!["bar", "baz", "foo"], names(&todos));
+}
+
+#[test]
+fn test_sort_by_multiple_keys() {
+    let mut todos = vec![
+        todo("foo", Status::Done, None, Some(Priority::High)),
+        todo("bar", Status::ToDo, None, Some(Priority::Low)),
+        todo("baz", Status::ToDo, None, Some(Priority::High)),
+        todo("qux", Status::ToDo, None, None),
+    ];
+
+    sort_todos(&mut todos, &parse_sort("status,priority:desc").unwrap());
+    assert_eq!(vec!["baz", "bar", "qux", "foo"], names(&todos));
+}
+
+#[test]
+fn test_sort_by_status() {
+    let mut todos = vec![
+        todo("foo", Status::Cancelled, None, None),
+        todo("bar", Status::Done, None, None),
+        todo("baz", Status::Blocked, None, None),
+        todo("qux", Status::InProgress, None, None),
+        todo("quux", Status::ToDo, None, None),
+    ];
+
+    sort_todos(&mut todos, &parse_sort("status").unwrap());
+    assert_eq!(vec!["quux", "qux", "baz", "bar", "foo"], names(&todos));
+}
+
+#[test]
+fn test_sort_by_name_descending() {
+    let mut todos = vec![
+        todo("bar", Status::ToDo, None, None),
+        todo("foo", Status::ToDo, None, None),
+    ];
+
+    sort_todos(&mut todos, &parse_sort("name:desc").unwrap());
+    assert_eq!(vec!["foo", "bar"], names(&todos));
+}
+
+fn todo(name: &str, status: Status, due_date: Option<Due>, priority: Option<Priority>) -> Todo {
+    Todo {
+        name: name.to_string(),
+        status,
+        due_date,
+        priority,
+        ..Default::default()
+    }
+}
+
+fn date(year: i32, month: u32, day: u32) -> Option<Due> {
+    NaiveDate::from_ymd_opt(year, month, day).map(Due::Date)
+}
+
+fn names(todos: &[Todo]) -> Vec<&str> {
+    todos.iter().map(|t| t.name.as_str()).collect()
+}
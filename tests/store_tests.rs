@@ -0,0 +1,195 @@
+use std::{cell::Cell, thread};
+
+use alle::store::{self, Batch, Column, Db, Entry, MemoryStore, StoreError, TodoStore};
+use alle::todo::{add_todo, add_todo_tag, get_todo};
+
+#[test]
+fn test_batch_reads_staged_values() {
+    let db = MemoryStore::new();
+    db.put(Column::Meta, b"key", b"stored").unwrap();
+
+    let mut batch = Batch::new();
+    assert_eq!(
+        Some(b"stored".to_vec()),
+        batch.read(&db, Column::Meta, b"key").unwrap()
+    );
+
+    batch.put(Column::Meta, b"key", b"staged");
+    assert_eq!(
+        Some(b"staged".to_vec()),
+        batch.read(&db, Column::Meta, b"key").unwrap()
+    );
+
+    batch.delete(Column::Meta, b"key");
+    assert_eq!(None, batch.read(&db, Column::Meta, b"key").unwrap());
+    assert_eq!(1, batch.reads().len());
+}
+
+#[test]
+fn test_scan_range() {
+    let db = MemoryStore::new();
+    for key in [b"a", b"b", b"c", b"d"] {
+        db.put(Column::ByDue, key, b"").unwrap();
+    }
+
+    let keys = |entries: Vec<(Vec<u8>, Vec<u8>)>| -> Vec<Vec<u8>> {
+        entries.into_iter().map(|(key, _)| key).collect()
+    };
+    assert_eq!(
+        vec![b"b".to_vec(), b"c".to_vec()],
+        keys(db.scan_range(Column::ByDue, b"b", Some(b"d")).unwrap())
+    );
+    assert_eq!(
+        vec![b"c".to_vec(), b"d".to_vec()],
+        keys(db.scan_range(Column::ByDue, b"bb", None).unwrap())
+    );
+    assert!(db
+        .scan_range(Column::ByDue, b"c", Some(b"a"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_write_fails_when_read_value_changed() {
+    let db = MemoryStore::new();
+    db.put(Column::Meta, b"key", b"old").unwrap();
+
+    let mut batch = Batch::new();
+    batch.read(&db, Column::Meta, b"key").unwrap();
+    batch.put(Column::Meta, b"key", b"mine");
+
+    db.put(Column::Meta, b"key", b"theirs").unwrap();
+
+    let result = db.write(batch);
+    assert!(result.unwrap_err().is_conflict());
+    assert_eq!(
+        Some(b"theirs".to_vec()),
+        db.get(Column::Meta, b"key").unwrap()
+    );
+}
+
+#[test]
+fn test_write_fails_when_read_key_was_created() {
+    let db = MemoryStore::new();
+
+    let mut batch = Batch::new();
+    batch.read(&db, Column::Todos, b"foo").unwrap();
+    batch.put(Column::Todos, b"foo", b"mine");
+
+    db.put(Column::Todos, b"foo", b"theirs").unwrap();
+
+    assert!(db.write(batch).unwrap_err().is_conflict());
+}
+
+// A RocksDB store that lets another writer change `foo` between the first batch
+// being built and written.
+struct Interrupted<'a> {
+    db: &'a Db,
+    interrupted: Cell<bool>,
+}
+
+impl TodoStore for Interrupted<'_> {
+    fn get(&self, column: Column, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        self.db.get(column, key)
+    }
+
+    fn put(&self, column: Column, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        self.db.put(column, key, value)
+    }
+
+    fn delete(&self, column: Column, key: &[u8]) -> Result<(), StoreError> {
+        self.db.delete(column, key)
+    }
+
+    fn scan(&self, column: Column) -> Result<Vec<Entry>, StoreError> {
+        self.db.scan(column)
+    }
+
+    fn scan_prefix(&self, column: Column, prefix: &[u8]) -> Result<Vec<Entry>, StoreError> {
+        self.db.scan_prefix(column, prefix)
+    }
+
+    fn scan_range(
+        &self,
+        column: Column,
+        from: &[u8],
+        to: Option<&[u8]>,
+    ) -> Result<Vec<Entry>, StoreError> {
+        self.db.scan_range(column, from, to)
+    }
+
+    fn write(&self, batch: Batch) -> Result<(), StoreError> {
+        if !self.interrupted.replace(true) {
+            add_todo_tag(self.db, "foo", "theirs").unwrap();
+        }
+        self.db.write(batch)
+    }
+}
+
+#[test]
+fn test_rocksdb_write_fails_when_read_value_changed() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = store::open(dir.path()).unwrap();
+    db.put(Column::Meta, b"key", b"old").unwrap();
+
+    let mut batch = Batch::new();
+    batch.read(&db, Column::Meta, b"key").unwrap();
+    batch.put(Column::Meta, b"key", b"mine");
+
+    db.put(Column::Meta, b"key", b"theirs").unwrap();
+
+    assert!(db.write(batch).unwrap_err().is_conflict());
+    assert_eq!(
+        Some(b"theirs".to_vec()),
+        db.get(Column::Meta, b"key").unwrap()
+    );
+}
+
+#[test]
+fn test_rocksdb_conflicting_update_is_retried() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = store::open(dir.path()).unwrap();
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+
+    let interrupted = Interrupted {
+        db: &db,
+        interrupted: Cell::new(false),
+    };
+    add_todo_tag(&interrupted, "foo", "mine").unwrap();
+
+    assert_eq!(vec!["theirs", "mine"], get_todo(&db, "foo").unwrap().tags);
+}
+
+#[test]
+fn test_rocksdb_scan_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = store::open(dir.path()).unwrap();
+    for key in [b"a", b"b", b"c"] {
+        db.put(Column::ByDue, key, b"").unwrap();
+    }
+
+    let keys: Vec<Vec<u8>> = db
+        .scan_range(Column::ByDue, b"b", Some(b"c"))
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(vec![b"b".to_vec()], keys);
+}
+
+#[test]
+fn test_concurrent_updates_are_not_lost() {
+    let db = MemoryStore::new();
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+
+    thread::scope(|scope| {
+        for i in 0..4 {
+            let db = &db;
+            scope.spawn(move || add_todo_tag(db, "foo", &format!("tag{}", i)).unwrap());
+        }
+    });
+
+    let mut tags = get_todo(&db, "foo").unwrap().tags;
+    tags.sort();
+    assert_eq!(vec!["tag0", "tag1", "tag2", "tag3"], tags);
+}
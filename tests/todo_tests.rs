@@ -1,797 +1,1213 @@
-use rocksdb::{Options, DB};
+use alle::date::{Due, DueFilter};
+use alle::store::{Column, MemoryStore, TodoStore};
 use alle::todo::{self, add_due_date, change_due_date, remove_due_date};
+use alle::{migrate_db, todo::SCHEMA_VERSION, Error};
+use chrono::{Duration, Local, NaiveDate, TimeZone};
 use todo::{
     add_todo, add_todo_note, add_todo_tag, complete_todo, delete_todo, edit_todo_note,
-    get_all_todos, remove_todo_note, remove_todo_tag, uncomplete_todo, Status, Todo,
+    filter_by_due, get_all_todos, get_todo, remove_todo_note, remove_todo_tag, rename_todo,
+    set_priority, set_recurrence, set_status, uncomplete_todo, Priority, Status, Todo,
 };
 
 #[test]
 fn test_add_todo_with_no_args() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let note_arg: Option<&String> = None;
-        let due_date_arg: Option<&String> = None;
-        let result = add_todo(&db, &key, note_arg, due_date_arg);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.name, key.to_string());
-        assert_eq!(todo.note, String::from(""));
-        assert_eq!(0, todo.tags.len())
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let note_arg: Option<&String> = None;
+    let due_date_arg: Option<&String> = None;
+    let result = add_todo(&db, &key, note_arg, due_date_arg, None, None);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.name, key.to_string());
+    assert_eq!(todo.note, String::from(""));
+    assert_eq!(0, todo.tags.len())
 }
 
 #[test]
 fn test_add_todo_with_note_and_due_date_args() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let note = String::from("whatever");
-        let note_arg: Option<&String> = Some(&note);
-
-        let due_date = String::from("17-07-2022");
-        let due_date_arg: Option<&String> = Some(&due_date);
-        let result = add_todo(&db, &key, note_arg, due_date_arg);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.name, key.to_string());
-        assert_eq!(todo.note, note);
-        assert_eq!(todo.due_date, due_date);
-        assert_eq!(0, todo.tags.len())
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let note = String::from("whatever");
+    let note_arg: Option<&String> = Some(&note);
+
+    let due_date = String::from("17-07-2022");
+    let due_date_arg: Option<&String> = Some(&due_date);
+    let result = add_todo(&db, &key, note_arg, due_date_arg, None, None);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.name, key.to_string());
+    assert_eq!(todo.note, note);
+    assert_eq!(
+        todo.due_date,
+        NaiveDate::from_ymd_opt(2022, 7, 17).map(Due::Date)
+    );
+    assert_eq!(0, todo.tags.len())
 }
 
 #[test]
 fn test_add_todo_already_exists() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(
-            &db,
-            &key,
-            Status::ToDo,
-            &due_date,
-            &String::from("no notes this time"),
-            &tags,
-        );
-
-        let note_arg: Option<&String> = None;
-        let due_date_arg: Option<&String> = None;
-        let result = add_todo(&db, &key, note_arg, due_date_arg);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name already exists");
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(
+        &db,
+        &key,
+        Status::ToDo,
+        due_date,
+        &String::from("no notes this time"),
+        &tags,
+    );
+
+    let note_arg: Option<&String> = None;
+    let due_date_arg: Option<&String> = None;
+    let result = add_todo(&db, &key, note_arg, due_date_arg, None, None);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::AlreadyExists)));
 }
 
 #[test]
 fn test_add_todo_invalid_due_date() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let note_arg: Option<&String> = None;
-        let due_date = String::from("17-07-222022");
-        let due_date_arg: Option<&String> = Some(&due_date);
-        let result = add_todo(&db, &key, note_arg, due_date_arg);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Invalid date format");
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let note_arg: Option<&String> = None;
+    let due_date = String::from("17-07-222022");
+    let due_date_arg: Option<&String> = Some(&due_date);
+    let result = add_todo(&db, &key, note_arg, due_date_arg, None, None);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::InvalidDate)));
 }
 
 #[test]
 fn test_list_all_todos_with_no_flags() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key1 = String::from("foo");
-        let note1 = String::from("random notes");
-        let due_date = String::from("");
-        let mut tags1: Vec<String> = Vec::new();
-        tags1.push(String::from("another tag"));
-        insert_todo(&db, &key1, Status::ToDo, &due_date, &note1, &tags1);
-
-        let key2 = String::from("bar");
-        let note2 = String::from("random notes again");
-        let mut tags2: Vec<String> = Vec::new();
-        tags2.push(String::from("random tag"));
-        insert_todo(&db, &key2, Status::Done, &due_date, &note2, &tags2);
-
-        // initialize status filter to be Option<&String>
-        let status_filter: Option<&String> = None;
-        let tag_filter: Option<&String> = None;
-        let todos = get_all_todos(&db, status_filter, tag_filter);
-        assert_eq!(2, todos.len());
-
-        let todo1 = todos.get(1).unwrap();
-        matches!(todo1.status, Status::Done);
-        assert_eq!(todo1.name, key1.to_string());
-        assert_eq!(todo1.note, note1);
-        assert_eq!(todo1.tags, tags1);
-
-        let todo2 = todos.get(0).unwrap();
-        assert_eq!(todo2.name, key2.to_string());
-        matches!(todo2.status, Status::ToDo);
-        assert_eq!(todo2.tags, tags2)
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key1 = String::from("foo");
+    let note1 = String::from("random notes");
+    let due_date = None;
+    let tags1: Vec<String> = vec![String::from("another tag")];
+    insert_todo(&db, &key1, Status::ToDo, due_date, &note1, &tags1);
+
+    let key2 = String::from("bar");
+    let note2 = String::from("random notes again");
+    let tags2: Vec<String> = vec![String::from("random tag")];
+    insert_todo(&db, &key2, Status::Done, due_date, &note2, &tags2);
+
+    // initialize status filter to be Option<&String>
+    let status_filter: Option<&String> = None;
+    let tag_filter: Option<&String> = None;
+    let todos = get_all_todos(&db, status_filter, tag_filter, None).unwrap();
+    assert_eq!(2, todos.len());
+
+    let todo1 = todos.get(1).unwrap();
+    matches!(todo1.status, Status::Done);
+    assert_eq!(todo1.name, key1.to_string());
+    assert_eq!(todo1.note, note1);
+    assert_eq!(todo1.tags, tags1);
+
+    let todo2 = todos.first().unwrap();
+    assert_eq!(todo2.name, key2.to_string());
+    matches!(todo2.status, Status::ToDo);
+    assert_eq!(todo2.tags, tags2)
 }
 
 #[test]
 fn test_list_all_todos_with_flags() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key1 = String::from("foo");
-        let note1 = String::from("random notes");
-        let due_date = String::from("");
-        let mut tags1: Vec<String> = Vec::new();
-        tags1.push(String::from("awesome"));
-        insert_todo(&db, &key1, Status::ToDo, &due_date, &note1, &tags1);
-
-        let key2 = String::from("bar");
-        let note2 = String::from("random notes again");
-        let mut tags2: Vec<String> = Vec::new();
-        tags2.push(String::from("random tag"));
-        insert_todo(&db, &key2, Status::Done, &due_date, &note2, &tags2);
-
-        let todo_status = String::from("ToDo");
-        let status_filter: Option<&String> = Some(&todo_status);
-
-        let awesome_tag = String::from("awesome");
-        let tag_filter: Option<&String> = Some(&awesome_tag);
-        let todos = get_all_todos(&db, status_filter, tag_filter);
-        assert_eq!(1, todos.len());
-
-        let todo1 = todos.get(0).unwrap();
-        matches!(todo1.status, Status::Done);
-        assert_eq!(todo1.name, key1.to_string());
-        assert_eq!(todo1.note, note1);
-        assert_eq!(todo1.tags, tags1);
-    }
+    let db = MemoryStore::new();
+
+    let key1 = String::from("foo");
+    let note1 = String::from("random notes");
+    let due_date = None;
+    let tags1: Vec<String> = vec![String::from("awesome")];
+    insert_todo(&db, &key1, Status::ToDo, due_date, &note1, &tags1);
+
+    let key2 = String::from("bar");
+    let note2 = String::from("random notes again");
+    let tags2: Vec<String> = vec![String::from("random tag")];
+    insert_todo(&db, &key2, Status::Done, due_date, &note2, &tags2);
+
+    let todo_status = String::from("ToDo");
+    let status_filter: Option<&String> = Some(&todo_status);
+
+    let awesome_tag = String::from("awesome");
+    let tag_filter: Option<&String> = Some(&awesome_tag);
+    let todos = get_all_todos(&db, status_filter, tag_filter, None).unwrap();
+    assert_eq!(1, todos.len());
+
+    let todo1 = todos.first().unwrap();
+    matches!(todo1.status, Status::Done);
+    assert_eq!(todo1.name, key1.to_string());
+    assert_eq!(todo1.note, note1);
+    assert_eq!(todo1.tags, tags1);
+}
+
+#[test]
+fn test_list_all_todos_with_invalid_status() {
+    let db = MemoryStore::new();
 
-    let _ = DB::destroy(&Options::default(), path);
+    let status = String::from("Someday");
+    let result = get_all_todos(&db, Some(&status), None, None);
+    assert!(matches!(result, Err(Error::InvalidStatus(_))));
 }
 
 #[test]
-fn test_complete_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+fn test_list_all_todos_with_corrupt_record() {
+    let db = MemoryStore::new();
 
-        let key = String::from("foo");
-        let note = String::from("whatever");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::ToDo, &due_date, &note, &tags);
+    db.put(Column::Todos, b"foo", b"{not json").unwrap();
 
-        let result = complete_todo(&db, &key);
-        assert_eq!(true, result.is_ok());
+    let result = get_all_todos(&db, None, None, None);
+    assert!(matches!(result, Err(Error::Corrupt(_))));
+}
 
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
+#[test]
+fn test_complete_todo() {
+    let db = MemoryStore::new();
 
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from("whatever"));
-    }
+    let key = String::from("foo");
+    let note = String::from("whatever");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::ToDo, due_date, &note, &tags);
+
+    let result = complete_todo(&db, &key);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
 
-    let _ = DB::destroy(&Options::default(), path);
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from("whatever"));
 }
 
 #[test]
 fn test_complete_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+    let db = MemoryStore::new();
 
-        let key = String::from("foo");
+    let key = String::from("foo");
 
-        let result = complete_todo(&db, &key);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
+    let result = complete_todo(&db, &key);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
+}
+
+#[test]
+fn test_parse_status_names_and_aliases() {
+    let cases = [
+        ("ToDo", Status::ToDo),
+        ("To Do", Status::ToDo),
+        ("open", Status::ToDo),
+        ("In Progress", Status::InProgress),
+        ("in_progress", Status::InProgress),
+        ("BLOCKED", Status::Blocked),
+        ("closed", Status::Done),
+        ("canceled", Status::Cancelled),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(expected, input.parse::<Status>().unwrap(), "{}", input);
     }
+    assert!(matches!(
+        "nope".parse::<Status>(),
+        Err(Error::InvalidStatus(_))
+    ));
+}
 
-    let _ = DB::destroy(&Options::default(), path);
+#[test]
+fn test_statuses_parse_from_their_display_form() {
+    for status in Status::ALL {
+        assert_eq!(status, status.to_string().parse::<Status>().unwrap());
+    }
 }
 
 #[test]
-fn test_uncomplete_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+fn test_set_status() {
+    let db = MemoryStore::new();
 
-        let key = String::from("foo");
-        let note = String::from("whatever");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &note, &tags);
+    let key = String::from("foo");
+    insert_todo(&db, &key, Status::ToDo, None, &String::new(), &[]);
 
-        let result = uncomplete_todo(&db, &key);
-        assert_eq!(true, result.is_ok());
+    set_status(&db, &key, "Blocked").unwrap();
+    let todos = get_all_todos(&db, Some(&String::from("Blocked")), None, None).unwrap();
+    assert_eq!(1, todos.len());
+    assert!(!todos[0].status.is_closed());
 
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
+    set_status(&db, &key, "Cancelled").unwrap();
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(Status::Cancelled, todos[0].status);
+    assert!(todos[0].status.is_closed());
+}
 
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from("whatever"));
-    }
+#[test]
+fn test_set_invalid_status() {
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    insert_todo(&db, &key, Status::ToDo, None, &String::new(), &[]);
 
-    let _ = DB::destroy(&Options::default(), path);
+    let result = set_status(&db, &key, "Waiting");
+    assert!(matches!(result, Err(Error::InvalidStatus(_))));
 }
 
 #[test]
-fn test_uncomplete_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+fn test_set_recurring_todo_done_completes_occurrence() {
+    let db = MemoryStore::new();
+
+    let due_date = String::from("2099-01-01");
+    let repeat = String::from("daily");
+    add_todo(&db, "foo", None, Some(&due_date), None, Some(&repeat)).unwrap();
+    set_status(&db, "foo", "InProgress").unwrap();
+    set_status(&db, "foo", "Done").unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(Status::ToDo, todos[0].status);
+    assert_eq!(1, todos[0].history.len());
+}
+
+#[test]
+fn test_uncomplete_todo() {
+    let db = MemoryStore::new();
 
-        let key = String::from("foo");
+    let key = String::from("foo");
+    let note = String::from("whatever");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &note, &tags);
 
-        let result = uncomplete_todo(&db, &key);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
+    let result = uncomplete_todo(&db, &key);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
 
-    let _ = DB::destroy(&Options::default(), path);
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from("whatever"));
+}
+
+#[test]
+fn test_uncomplete_missing_todo() {
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+
+    let result = uncomplete_todo(&db, &key);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_add_todo_note() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let note = String::from("");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &note, &tags);
-
-        let new_note = String::from("new note");
-        let result = add_todo_note(&db, &key, &new_note);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from("new note"));
-    }
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let note = String::from("");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &note, &tags);
+
+    let new_note = String::from("new note");
+    let result = add_todo_note(&db, &key, &new_note);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
 
-    let _ = DB::destroy(&Options::default(), path);
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from("new note"));
 }
 
 #[test]
 fn test_add_todo_note_with_existing_note() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let note = String::from("random note");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &note, &tags);
-
-        let result = add_todo_note(&db, &key, &note);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "This todo already has a note");
-    }
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let note = String::from("random note");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &note, &tags);
 
-    let _ = DB::destroy(&Options::default(), path);
+    let result = add_todo_note(&db, &key, &note);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NoteAlreadyExists)));
 }
 
 #[test]
 fn test_add_todo_note_with_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let note = String::from("random note");
+    let db = MemoryStore::new();
 
-        let result = add_todo_note(&db, &key, &note);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
+    let key = String::from("foo");
+    let note = String::from("random note");
 
-    let _ = DB::destroy(&Options::default(), path);
+    let result = add_todo_note(&db, &key, &note);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_edit_todo_note() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let note = String::from("original note");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &note, &tags);
-
-        let new_note = String::from("new note");
-        let result = edit_todo_note(&db, &key, &new_note);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from("new note"));
-    }
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let note = String::from("original note");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &note, &tags);
 
-    let _ = DB::destroy(&Options::default(), path);
+    let new_note = String::from("new note");
+    let result = edit_todo_note(&db, &key, &new_note);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
+
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from("new note"));
 }
 
 #[test]
 fn test_edit_todo_note_with_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+    let db = MemoryStore::new();
 
-        let key = String::from("foo");
-        let new_note = String::from("random note");
-
-        let result = edit_todo_note(&db, &key, &new_note);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
+    let key = String::from("foo");
+    let new_note = String::from("random note");
 
-    let _ = DB::destroy(&Options::default(), path);
+    let result = edit_todo_note(&db, &key, &new_note);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_remove_todo_note() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+    let db = MemoryStore::new();
 
-        let key = String::from("foo");
-        let note = String::from("original note");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &note, &tags);
+    let key = String::from("foo");
+    let note = String::from("original note");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &note, &tags);
 
-        let result = remove_todo_note(&db, &key);
-        assert_eq!(true, result.is_ok());
+    let result = remove_todo_note(&db, &key);
+    assert!(result.is_ok());
 
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
 
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from(""));
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from(""));
 }
 
 #[test]
 fn test_remove_todo_note_with_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
+    let db = MemoryStore::new();
 
-        let result = remove_todo_note(&db, &key);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
+    let key = String::from("foo");
 
-    let _ = DB::destroy(&Options::default(), path);
+    let result = remove_todo_note(&db, &key);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_add_tag_to_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let tags = Vec::new();
-        let tag = String::from("random tag");
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let result = add_todo_tag(&db, &key, &tag);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from(""));
-        assert_eq!(1, todo.tags.len());
-        assert_eq!(&tag, todo.tags.get(0).unwrap())
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tags = Vec::new();
+    let tag = String::from("random tag");
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let result = add_todo_tag(&db, &key, &tag);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
+
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from(""));
+    assert_eq!(1, todo.tags.len());
+    assert_eq!(&tag, todo.tags.first().unwrap())
 }
 
 #[test]
 fn test_add_tag_to_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+    let db = MemoryStore::new();
 
-        let key = String::from("foo");
-        let tag = String::from("random tag");
+    let key = String::from("foo");
+    let tag = String::from("random tag");
 
-        let result = add_todo_tag(&db, &key, &tag);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let result = add_todo_tag(&db, &key, &tag);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_add_tag_that_already_exists_to_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let mut tags = Vec::new();
-        let tag = String::from("randomt tag");
-        tags.push(tag.to_string());
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let result = add_todo_tag(&db, &key, &tag);
-        assert_eq!(true, result.is_err());
-        assert_eq!(
-            result.err().unwrap(),
-            "This tag is has already been added to this todo"
-        );
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tag = String::from("randomt tag");
+    let tags = vec![tag.to_string()];
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let result = add_todo_tag(&db, &key, &tag);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::TagAlreadyExists)));
 }
 
 #[test]
 fn test_remove_todo_tag() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let mut tags = Vec::new();
-        let tag = String::from("random tag");
-        tags.push(tag.to_string());
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let result = remove_todo_tag(&db, &key, &tag);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from(""));
-        assert_eq!(0, todo.tags.len());
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tag = String::from("random tag");
+    let tags = vec![tag.to_string()];
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let result = remove_todo_tag(&db, &key, &tag);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
+
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from(""));
+    assert_eq!(0, todo.tags.len());
 }
 
 #[test]
 fn test_remove_todo_tag_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+    let db = MemoryStore::new();
 
-        let key = String::from("foo");
-        let tag = String::from("random tag");
-
-        let result = remove_todo_tag(&db, &key, &tag);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
+    let key = String::from("foo");
+    let tag = String::from("random tag");
 
-    let _ = DB::destroy(&Options::default(), path);
+    let result = remove_todo_tag(&db, &key, &tag);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_remove_missing_todo_tag() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let mut tags = Vec::new();
-        let tag = String::from("random tag");
-        tags.push(tag.to_string());
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let missing_tag = String::from("missing tag");
-
-        let result = remove_todo_tag(&db, &key, &missing_tag);
-        assert_eq!(true, result.is_err());
-        assert_eq!(
-            result.err().unwrap(),
-            "This tag does not exist for this todo"
-        );
-    }
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tag = String::from("random tag");
+    let tags = vec![tag.to_string()];
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let missing_tag = String::from("missing tag");
 
-    let _ = DB::destroy(&Options::default(), path);
+    let result = remove_todo_tag(&db, &key, &missing_tag);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::TagNotFound)));
 }
 
 #[test]
 fn test_add_due_date_for_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let new_due_date = String::from("17-07-2022");
-        let result = add_due_date(&db, &key, &new_due_date);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from(""));
-        assert_eq!(todo.due_date, new_due_date);
-        assert_eq!(0, todo.tags.len());
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let new_due_date = String::from("17-07-2022");
+    let result = add_due_date(&db, &key, &new_due_date);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
+
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from(""));
+    assert_eq!(
+        todo.due_date,
+        NaiveDate::from_ymd_opt(2022, 7, 17).map(Due::Date)
+    );
+    assert_eq!(0, todo.tags.len());
 }
 
 #[test]
 fn test_add_invalid_due_date_for_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let new_due_date = String::from("17-07-202222");
-        let result = add_due_date(&db, &key, &new_due_date);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Invalid date format");
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let new_due_date = String::from("17-07-202222");
+    let result = add_due_date(&db, &key, &new_due_date);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::InvalidDate)));
 }
 
 #[test]
 fn test_add_due_date_for_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let due_date = String::from("17-07-2022");
-        let result = add_due_date(&db, &key, &due_date);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
+    let db = MemoryStore::new();
 
-    let _ = DB::destroy(&Options::default(), path);
+    let key = String::from("foo");
+    let due_date = String::from("17-07-2022");
+    let result = add_due_date(&db, &key, &due_date);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_change_due_date_for_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let new_due_date = String::from("17-07-2022");
-        let result = change_due_date(&db, &key, &new_due_date);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from(""));
-        assert_eq!(todo.due_date, new_due_date);
-        assert_eq!(0, todo.tags.len());
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let new_due_date = String::from("17-07-2022");
+    let result = change_due_date(&db, &key, &new_due_date);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
+
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from(""));
+    assert_eq!(
+        todo.due_date,
+        NaiveDate::from_ymd_opt(2022, 7, 17).map(Due::Date)
+    );
+    assert_eq!(0, todo.tags.len());
 }
 
 #[test]
 fn test_change_invalid_due_date_for_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let new_due_date = String::from("17-07-202222");
-        let result = change_due_date(&db, &key, &new_due_date);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Invalid date format");
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let new_due_date = String::from("17-07-202222");
+    let result = change_due_date(&db, &key, &new_due_date);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::InvalidDate)));
 }
 
 #[test]
 fn test_change_due_date_for_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let due_date = String::from("17-07-2022");
-        let result = change_due_date(&db, &key, &due_date);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
+    let db = MemoryStore::new();
 
-    let _ = DB::destroy(&Options::default(), path);
+    let key = String::from("foo");
+    let due_date = String::from("17-07-2022");
+    let result = change_due_date(&db, &key, &due_date);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_remove_due_date_for_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::Done, &due_date, &notes, &tags);
-
-        let result = remove_due_date(&db, &key);
-        assert_eq!(true, result.is_ok());
-
-        let db_value = String::from_utf8(db.get(&key).unwrap().unwrap()).unwrap();
-        let todo: Todo = serde_json::from_str(&db_value).unwrap();
-
-        assert_eq!(todo.name, key.to_string());
-        matches!(todo.status, Status::ToDo);
-        assert_eq!(todo.note, String::from(""));
-        assert_eq!(todo.due_date, String::from(""));
-        assert_eq!(0, todo.tags.len());
-    }
-
-    let _ = DB::destroy(&Options::default(), path);
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let notes = String::from("");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::Done, due_date, &notes, &tags);
+
+    let result = remove_due_date(&db, &key);
+    assert!(result.is_ok());
+
+    let db_value =
+        String::from_utf8(db.get(Column::Todos, key.as_bytes()).unwrap().unwrap()).unwrap();
+    let todo: Todo = serde_json::from_str(&db_value).unwrap();
+
+    assert_eq!(todo.name, key.to_string());
+    matches!(todo.status, Status::ToDo);
+    assert_eq!(todo.note, String::from(""));
+    assert_eq!(todo.due_date, None);
+    assert_eq!(0, todo.tags.len());
 }
 
 #[test]
 fn test_remove_due_date_for_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
+    let db = MemoryStore::new();
 
-        let result = remove_due_date(&db, &key);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
-    }
+    let key = String::from("foo");
 
-    let _ = DB::destroy(&Options::default(), path);
+    let result = remove_due_date(&db, &key);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
 }
 
 #[test]
 fn test_delete_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
-
-        let key = String::from("foo");
-        let notes = String::from("whatever");
-        let tags = Vec::new();
-        let due_date = String::from("");
-        insert_todo(&db, &key, Status::ToDo, &due_date, &notes, &tags);
+    let db = MemoryStore::new();
 
-        let result = delete_todo(&db, &key);
-        assert_eq!(true, result.is_ok());
+    let key = String::from("foo");
+    let notes = String::from("whatever");
+    let tags = Vec::new();
+    let due_date = None;
+    insert_todo(&db, &key, Status::ToDo, due_date, &notes, &tags);
 
-        let db_value = db.get(key).unwrap();
-        assert_eq!(true, db_value.is_none());
-    }
+    let result = delete_todo(&db, &key);
+    assert!(result.is_ok());
 
-    let _ = DB::destroy(&Options::default(), path);
+    let db_value = db.get(Column::Todos, key.as_bytes()).unwrap();
+    assert!(db_value.is_none());
 }
 
 #[test]
 fn test_delete_missing_todo() {
-    let path = "/tmp";
-    {
-        let db = DB::open_default(path).unwrap();
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+
+    let result = delete_todo(&db, &key);
+    assert!(result.is_err());
+    assert!(matches!(result, Err(Error::NotFound)));
+}
+
+#[test]
+fn test_add_todo_assigns_incrementing_ids() {
+    let db = MemoryStore::new();
+
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+    add_todo(&db, "bar", None, None, None, None).unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    let bar = todos.iter().find(|t| t.name == "bar").unwrap();
+    let foo = todos.iter().find(|t| t.name == "foo").unwrap();
+    assert_eq!(1, foo.id);
+    assert_eq!(2, bar.id);
+}
+
+#[test]
+fn test_complete_todo_by_id() {
+    let db = MemoryStore::new();
+
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+
+    let result = complete_todo(&db, "1");
+    assert!(result.is_ok());
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(Status::Done, todos.first().unwrap().status);
+}
+
+#[test]
+fn test_numeric_name_takes_precedence_over_id() {
+    let db = MemoryStore::new();
+
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+    add_todo(&db, "1", None, None, None, None).unwrap();
 
-        let key = String::from("foo");
+    complete_todo(&db, "1").unwrap();
 
-        let result = delete_todo(&db, &key);
-        assert_eq!(true, result.is_err());
-        assert_eq!(result.err().unwrap(), "Todo with this name does not exist");
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    let numeric = todos.iter().find(|t| t.name == "1").unwrap();
+    let foo = todos.iter().find(|t| t.name == "foo").unwrap();
+    assert_eq!(Status::Done, numeric.status);
+    assert_eq!(Status::ToDo, foo.status);
+}
+
+#[test]
+fn test_ids_are_not_reused_after_delete() {
+    let db = MemoryStore::new();
+
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+    delete_todo(&db, "#1").unwrap();
+    add_todo(&db, "bar", None, None, None, None).unwrap();
+
+    assert!(matches!(complete_todo(&db, "1"), Err(Error::NotFound)));
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(2, todos.first().unwrap().id);
+}
+
+#[test]
+fn test_legacy_todo_gets_id_on_update() {
+    let db = MemoryStore::new();
+
+    let key = String::from("foo");
+    let tags = Vec::new();
+    let empty = String::from("");
+    insert_todo(&db, &key, Status::ToDo, None, &empty, &tags);
+
+    complete_todo(&db, &key).unwrap();
+    complete_todo(&db, "1").unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(1, todos.first().unwrap().id);
+}
+
+#[test]
+fn test_rename_todo() {
+    let db = MemoryStore::new();
+
+    let note = String::from("whatever");
+    let due_date = String::from("17-07-2022");
+    add_todo(&db, "foo", Some(&note), Some(&due_date), None, None).unwrap();
+    add_todo_tag(&db, "foo", "work").unwrap();
+    complete_todo(&db, "foo").unwrap();
+
+    let result = rename_todo(&db, "foo", "bar");
+    assert!(result.is_ok());
+    assert!(db.get(Column::Todos, b"foo").unwrap().is_none());
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(1, todos.len());
+    let todo = todos.first().unwrap();
+    assert_eq!(1, todo.id);
+    assert_eq!("bar", todo.name);
+    assert_eq!(Status::Done, todo.status);
+    assert_eq!(note, todo.note);
+    assert_eq!(
+        NaiveDate::from_ymd_opt(2022, 7, 17).map(Due::Date),
+        todo.due_date
+    );
+    assert_eq!(vec![String::from("work")], todo.tags);
+
+    assert!(uncomplete_todo(&db, "1").is_ok());
+}
+
+#[test]
+fn test_rename_todo_to_existing_name() {
+    let db = MemoryStore::new();
+
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+    add_todo(&db, "bar", None, None, None, None).unwrap();
+
+    let result = rename_todo(&db, "foo", "bar");
+    assert!(matches!(result, Err(Error::AlreadyExists)));
+    assert_eq!(2, get_all_todos(&db, None, None, None).unwrap().len());
+}
+
+#[test]
+fn test_todos_need_a_name() {
+    let db = MemoryStore::new();
+
+    for name in ["", "  "] {
+        let result = add_todo(&db, name, None, None, None, None);
+        assert!(matches!(result, Err(Error::EmptyName)));
     }
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+    assert!(matches!(
+        rename_todo(&db, "foo", " "),
+        Err(Error::EmptyName)
+    ));
+    assert_eq!("foo", get_todo(&db, "1").unwrap().name);
+}
+
+#[test]
+fn test_rename_missing_todo() {
+    let db = MemoryStore::new();
+
+    let result = rename_todo(&db, "foo", "bar");
+    assert!(matches!(result, Err(Error::NotFound)));
+}
+
+#[test]
+fn test_migrate_legacy_todos() {
+    let db = MemoryStore::new();
+
+    let legacy = r#"{"name":"foo","status":"Done","due_date":"","note":"old","tags":["a"]}"#;
+    db.put(Column::Todos, b"foo", legacy.as_bytes()).unwrap();
+    let legacy = r#"{"name":"bar","status":"ToDo","due_date":"","note":"","tags":[]}"#;
+    db.put(Column::Todos, b"bar", legacy.as_bytes()).unwrap();
+
+    assert_eq!(2, migrate_db(&db).unwrap());
+    assert_eq!(0, migrate_db(&db).unwrap());
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    let bar = todos.iter().find(|t| t.name == "bar").unwrap();
+    let foo = todos.iter().find(|t| t.name == "foo").unwrap();
+    assert_eq!(SCHEMA_VERSION, foo.version);
+    assert_eq!(SCHEMA_VERSION, bar.version);
+    assert_eq!(1, bar.id);
+    assert_eq!(2, foo.id);
+    assert_eq!(Status::Done, foo.status);
+    assert_eq!("old", foo.note);
+    assert!(complete_todo(&db, "1").is_ok());
+}
+
+#[test]
+fn test_decode_todo_with_missing_fields() {
+    let db = MemoryStore::new();
+
+    db.put(Column::Todos, b"foo", br#"{"name":"foo"}"#).unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    let todo = todos.first().unwrap();
+    assert_eq!("foo", todo.name);
+    assert_eq!(Status::ToDo, todo.status);
+    assert_eq!(0, todo.tags.len());
+}
+
+#[test]
+fn test_decode_todo_with_legacy_due_date() {
+    let db = MemoryStore::new();
+
+    let legacy = r#"{"name":"foo","status":"ToDo","due_date":"17-07-2022","note":"","tags":[]}"#;
+    db.put(Column::Todos, b"foo", legacy.as_bytes()).unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(
+        NaiveDate::from_ymd_opt(2022, 7, 17).map(Due::Date),
+        todos.first().unwrap().due_date
+    );
+}
+
+#[test]
+fn test_add_todo_with_iso_due_date() {
+    let db = MemoryStore::new();
+
+    let due_date = String::from("2026-11-01");
+    add_todo(&db, "foo", None, Some(&due_date), None, None).unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(
+        NaiveDate::from_ymd_opt(2026, 11, 1).map(Due::Date),
+        todos.first().unwrap().due_date
+    );
+}
+
+#[test]
+fn test_add_todo_with_due_time_and_zone() {
+    let db = MemoryStore::new();
+
+    let due_date = String::from("2026-11-01 09:30 +02:00");
+    add_todo(&db, "foo", None, Some(&due_date), None, None).unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    let due = todos.first().unwrap().due_date.unwrap();
+    assert_eq!("2026-11-01T09:30:00+02:00", due.to_string());
+}
+
+#[test]
+fn test_decode_todo_with_due_time() {
+    let db = MemoryStore::new();
+
+    let record = r#"{"name":"foo","due_date":"2026-11-01T07:30:00Z"}"#;
+    db.put(Column::Todos, b"foo", record.as_bytes()).unwrap();
 
-    let _ = DB::destroy(&Options::default(), path);
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    let due = todos.first().unwrap().due_date.unwrap();
+    assert_eq!("2026-11-01T07:30:00+00:00", due.to_string());
+}
+
+#[test]
+fn test_decode_todo_from_newer_schema() {
+    let db = MemoryStore::new();
+
+    let record = format!(r#"{{"version":{},"name":"foo"}}"#, SCHEMA_VERSION + 1);
+    db.put(Column::Todos, b"foo", record.as_bytes()).unwrap();
+
+    let result = complete_todo(&db, "foo");
+    assert!(matches!(result, Err(Error::UnsupportedVersion(_))));
+}
+
+#[test]
+fn test_add_todo_with_priority() {
+    let db = MemoryStore::new();
+
+    let priority = String::from("HIGH");
+    add_todo(&db, "foo", None, None, Some(&priority), None).unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(Some(Priority::High), todos.first().unwrap().priority);
+}
+
+#[test]
+fn test_add_todo_with_invalid_priority() {
+    let db = MemoryStore::new();
+
+    let priority = String::from("urgent");
+    let result = add_todo(&db, "foo", None, None, Some(&priority), None);
+    assert!(matches!(result, Err(Error::InvalidPriority(_))));
+    assert!(db.get(Column::Todos, b"foo").unwrap().is_none());
+}
+
+#[test]
+fn test_set_priority() {
+    let db = MemoryStore::new();
+
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+
+    assert!(set_priority(&db, "foo", "4").is_ok());
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(Some(Priority::Critical), todos.first().unwrap().priority);
+
+    assert!(set_priority(&db, "foo", "none").is_ok());
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(None, todos.first().unwrap().priority);
+}
+
+#[test]
+fn test_set_priority_for_missing_todo() {
+    let db = MemoryStore::new();
+
+    let result = set_priority(&db, "foo", "low");
+    assert!(matches!(result, Err(Error::NotFound)));
+}
+
+#[test]
+fn test_list_all_todos_with_priority_filter() {
+    let db = MemoryStore::new();
+
+    let low = String::from("low");
+    let high = String::from("high");
+    add_todo(&db, "foo", None, None, Some(&low), None).unwrap();
+    add_todo(&db, "bar", None, None, Some(&high), None).unwrap();
+    add_todo(&db, "baz", None, None, None, None).unwrap();
+
+    let todos = get_all_todos(&db, None, None, Some(&high)).unwrap();
+    assert_eq!(1, todos.len());
+    assert_eq!("bar", todos.first().unwrap().name);
+}
+
+#[test]
+fn test_complete_recurring_todo() {
+    let db = MemoryStore::new();
+
+    let due_date = String::from("2099-01-01");
+    let repeat = String::from("weekly");
+    add_todo(&db, "foo", None, Some(&due_date), None, Some(&repeat)).unwrap();
+    complete_todo(&db, "foo").unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    let todo = todos.first().unwrap();
+    assert_eq!(Status::ToDo, todo.status);
+    assert_eq!(
+        NaiveDate::from_ymd_opt(2099, 1, 8).map(Due::Date),
+        todo.due_date
+    );
+    assert_eq!(1, todo.history.len());
+    assert_eq!(
+        NaiveDate::from_ymd_opt(2099, 1, 1).map(Due::Date),
+        todo.history[0].due_date
+    );
+}
+
+#[test]
+fn test_moved_due_date_starts_a_new_series() {
+    let db = MemoryStore::new();
+
+    let due_date = String::from("2099-01-15");
+    let repeat = String::from("monthly");
+    add_todo(&db, "foo", None, Some(&due_date), None, Some(&repeat)).unwrap();
+    complete_todo(&db, "foo").unwrap();
+    change_due_date(&db, "foo", "2099-01-31").unwrap();
+
+    let dates: Vec<String> = (0..2)
+        .map(|_| {
+            complete_todo(&db, "foo")
+                .unwrap()
+                .due_date
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(vec!["2099-02-28", "2099-03-31"], dates);
+}
+
+#[test]
+fn test_uncomplete_recurring_todo_undoes_last_completion() {
+    let db = MemoryStore::new();
+
+    let due_date = String::from("2099-01-01");
+    let repeat = String::from("daily");
+    add_todo(&db, "foo", None, Some(&due_date), None, Some(&repeat)).unwrap();
+    complete_todo(&db, "foo").unwrap();
+    uncomplete_todo(&db, "foo").unwrap();
+
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    let todo = todos.first().unwrap();
+    assert_eq!(
+        NaiveDate::from_ymd_opt(2099, 1, 1).map(Due::Date),
+        todo.due_date
+    );
+    assert!(todo.history.is_empty());
+
+    assert!(matches!(
+        uncomplete_todo(&db, "foo"),
+        Err(Error::NotCompleted)
+    ));
+}
+
+#[test]
+fn test_uncomplete_recurring_todo_after_due_date_change() {
+    let db = MemoryStore::new();
+
+    let due_date = String::from("2099-01-01");
+    let repeat = String::from("daily");
+    add_todo(&db, "foo", None, Some(&due_date), None, Some(&repeat)).unwrap();
+    complete_todo(&db, "foo").unwrap();
+    change_due_date(&db, "foo", "2099-02-01").unwrap();
+
+    assert!(matches!(
+        uncomplete_todo(&db, "foo"),
+        Err(Error::NotCompleted)
+    ));
+    let todo = get_todo(&db, "foo").unwrap();
+    assert_eq!(1, todo.history.len());
+    assert_eq!(
+        NaiveDate::from_ymd_opt(2099, 2, 1).map(Due::Date),
+        todo.due_date
+    );
+}
+
+#[test]
+fn test_set_recurrence() {
+    let db = MemoryStore::new();
+
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+    set_recurrence(&db, "foo", "every 2 weeks").unwrap();
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(
+        "every 2 weeks",
+        todos.first().unwrap().recurrence.unwrap().to_string()
+    );
+
+    set_recurrence(&db, "foo", "none").unwrap();
+    let todos = get_all_todos(&db, None, None, None).unwrap();
+    assert_eq!(None, todos.first().unwrap().recurrence);
+}
+
+#[test]
+fn test_set_invalid_recurrence() {
+    let db = MemoryStore::new();
+
+    add_todo(&db, "foo", None, None, None, None).unwrap();
+    let result = set_recurrence(&db, "foo", "sometimes");
+    assert!(matches!(result, Err(Error::InvalidRecurrence(_))));
+}
+
+#[test]
+fn test_filter_todos_by_due_date() {
+    let db = MemoryStore::new();
+    let empty = String::new();
+    let due = |day| NaiveDate::from_ymd_opt(2026, 10, day).map(Due::Date);
+    insert_todo(
+        &db,
+        &String::from("late"),
+        Status::ToDo,
+        due(10),
+        &empty,
+        &[],
+    );
+    insert_todo(
+        &db,
+        &String::from("finished"),
+        Status::Done,
+        due(10),
+        &empty,
+        &[],
+    );
+    insert_todo(
+        &db,
+        &String::from("today"),
+        Status::ToDo,
+        due(14),
+        &empty,
+        &[],
+    );
+    insert_todo(
+        &db,
+        &String::from("soon"),
+        Status::ToDo,
+        due(16),
+        &empty,
+        &[],
+    );
+    insert_todo(
+        &db,
+        &String::from("someday"),
+        Status::ToDo,
+        None,
+        &empty,
+        &[],
+    );
+
+    let now = Local
+        .from_local_datetime(
+            &NaiveDate::from_ymd_opt(2026, 10, 14)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+        )
+        .unwrap();
+    let filtered = |filter| {
+        let mut todos = get_all_todos(&db, None, None, None).unwrap();
+        filter_by_due(&mut todos, filter, now);
+        todos.into_iter().map(|t| t.name).collect::<Vec<_>>()
+    };
+
+    assert_eq!(vec!["late"], filtered(DueFilter::Overdue));
+    assert_eq!(vec!["today"], filtered(DueFilter::Today));
+    assert_eq!(
+        vec!["soon", "today"],
+        filtered(DueFilter::Within(Duration::days(3)))
+    );
 }
 
 fn insert_todo(
-    db: &DB,
+    db: &MemoryStore,
     key: &String,
     status: Status,
-    due_date: &String,
+    due_date: Option<Due>,
     notes: &String,
-    tags: &Vec<String>,
+    tags: &[String],
 ) {
     let todo = Todo {
         name: key.to_string(),
         status,
-        due_date: due_date.to_string(),
+        due_date,
         note: notes.to_string(),
         tags: tags.to_vec(),
+        ..Default::default()
     };
     let serialized = serde_json::to_string(&todo).unwrap();
-    db.put(&key, serialized).unwrap();
-}
-
-#[test]
-#[ignore]
-fn destroy_db() {
-    let path = "/tmp";
-    let _ = DB::destroy(&Options::default(), path);
+    db.put(Column::Todos, key.as_bytes(), serialized.as_bytes())
+        .unwrap();
 }
@@ -0,0 +1,146 @@
+use alle::date::Due;
+use alle::store::MemoryStore;
+use alle::todo::{
+    add_todo, add_todo_tag, complete_todo, get_todo, import_todos, set_status, ImportMode,
+    Priority, Status, Todo,
+};
+use alle::todotxt::{format_todo, parse_line, read_todos, write_todos};
+use alle::Error;
+use chrono::NaiveDate;
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_parse_line() {
+    let todo = parse_line("(A) 2026-10-01 Call mom +family @phone due:2026-10-20").unwrap();
+
+    assert_eq!("Call mom", todo.name);
+    assert_eq!(Status::ToDo, todo.status);
+    assert_eq!(Some(Priority::Critical), todo.priority);
+    assert_eq!(vec!["family", "phone"], todo.tags);
+    assert_eq!(Some(Due::Date(date("2026-10-20"))), todo.due_date);
+    assert!(todo.created_at.is_some());
+}
+
+#[test]
+fn test_parse_completed_line() {
+    let todo = parse_line("x 2026-10-18 2026-10-01 Buy milk pri:E url:http://a").unwrap();
+
+    assert_eq!("Buy milk url:http://a", todo.name);
+    assert_eq!(Status::Done, todo.status);
+    assert_eq!(Some(Priority::Low), todo.priority);
+    assert!(todo.created_at.is_some());
+
+    let todo = parse_line("x 2026-10-18 Buy milk").unwrap();
+    assert_eq!("Buy milk", todo.name);
+    assert!(todo.created_at.is_none());
+}
+
+#[test]
+fn test_parse_invalid_line() {
+    assert!(matches!(
+        parse_line("Buy milk due:tomorrow"),
+        Err(Error::InvalidDate)
+    ));
+    assert!(matches!(
+        read_todos("ok\nBuy milk pri:AA\n".as_bytes()),
+        Err(Error::InvalidImport(e)) if e.starts_with("line 2")
+    ));
+}
+
+#[test]
+fn test_format_todo() {
+    let todo = Todo {
+        name: String::from("Write report"),
+        status: Status::Cancelled,
+        priority: Some(Priority::High),
+        tags: vec![String::from("work")],
+        due_date: Some(Due::Date(date("2026-10-20"))),
+        ..Todo::default()
+    };
+
+    assert_eq!(
+        "x Write report +work due:2026-10-20 pri:B status:cancelled",
+        format_todo(&todo).unwrap()
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let db = MemoryStore::new();
+    let due = String::from("2030-01-02");
+    let priority = String::from("medium");
+    add_todo(&db, "foo", None, Some(&due), Some(&priority), None).unwrap();
+    add_todo(&db, "bar", None, None, Some(&priority), None).unwrap();
+    add_todo(&db, "baz", None, None, None, None).unwrap();
+    add_todo_tag(&db, "foo", "home").unwrap();
+    complete_todo(&db, "bar").unwrap();
+    set_status(&db, "baz", "blocked").unwrap();
+    let todos = vec![
+        get_todo(&db, "foo").unwrap(),
+        get_todo(&db, "bar").unwrap(),
+        get_todo(&db, "baz").unwrap(),
+    ];
+
+    let mut out = Vec::new();
+    write_todos(&mut out, &todos).unwrap();
+    let read = read_todos(out.as_slice()).unwrap();
+
+    for (todo, read) in todos.iter().zip(&read) {
+        assert_eq!(todo.name, read.name);
+        assert_eq!(todo.status, read.status);
+        assert_eq!(todo.priority, read.priority);
+        assert_eq!(todo.tags, read.tags);
+        assert_eq!(todo.due_date, read.due_date);
+    }
+
+    let copy = MemoryStore::new();
+    import_todos(&copy, &read, ImportMode::Merge).unwrap();
+    let mut again = Vec::new();
+    write_todos(&mut again, &read).unwrap();
+    assert_eq!(out, again);
+    assert_eq!(Status::Blocked, get_todo(&copy, "baz").unwrap().status);
+}
+
+#[test]
+fn test_names_that_look_like_markers_round_trip() {
+    for (name, status) in [
+        ("x marks the spot", Status::ToDo),
+        ("(A) is the best grade", Status::ToDo),
+        ("2026-10-20 retro", Status::ToDo),
+        ("2026-10-20 retro", Status::Done),
+        ("Ask @anna about +1s", Status::ToDo),
+        ("Fix due:tomorrow and status:done parsing", Status::ToDo),
+        ("Escape \\n and \\+ in paths", Status::ToDo),
+    ] {
+        let todo = Todo {
+            name: name.to_string(),
+            status,
+            tags: vec![String::from("work")],
+            ..Todo::default()
+        };
+
+        let read = parse_line(&format_todo(&todo).unwrap()).unwrap();
+        assert_eq!(name, read.name);
+        assert_eq!(status, read.status);
+        assert_eq!(vec!["work"], read.tags);
+        assert_eq!(None, read.priority);
+        assert_eq!(None, read.created_at);
+    }
+}
+
+#[test]
+fn test_tags_with_spaces_are_not_exported() {
+    let todo = Todo {
+        name: String::from("Call mom"),
+        tags: vec![String::from("family"), String::from("phone calls")],
+        ..Todo::default()
+    };
+
+    assert!(matches!(
+        format_todo(&todo),
+        Err(Error::InvalidExport(e)) if e.contains("phone calls")
+    ));
+}
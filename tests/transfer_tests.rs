@@ -0,0 +1,153 @@
+use std::path::Path;
+
+use alle::store::MemoryStore;
+use alle::todo::{
+    add_todo, add_todo_tag, complete_todo, get_todo, import_todos, ImportMode, ImportReport,
+    Status, Todo, SCHEMA_VERSION,
+};
+use alle::transfer::{export_todos, read_todos, Format};
+use alle::Error;
+
+fn store() -> MemoryStore {
+    let db = MemoryStore::new();
+    let due = String::from("2030-01-02");
+    let priority = String::from("high");
+    add_todo(&db, "foo", None, Some(&due), Some(&priority), None).unwrap();
+    add_todo(&db, "bar", None, None, None, None).unwrap();
+    add_todo_tag(&db, "foo", "a").unwrap();
+    add_todo_tag(&db, "foo", "b, \"c\"").unwrap();
+    complete_todo(&db, "bar").unwrap();
+    db
+}
+
+fn exported(db: &MemoryStore, format: Format) -> Vec<Todo> {
+    let mut out = Vec::new();
+    export_todos(db, &mut out, format).unwrap();
+    read_todos(out.as_slice(), format).unwrap()
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_format_from_path() {
+    assert_eq!(Some(Format::Csv), Format::from_path(Path::new("todos.CSV")));
+    assert_eq!(
+        Some(Format::Jsonl),
+        Format::from_path(Path::new("a/todos.jsonl"))
+    );
+    assert_eq!(None, Format::from_path(Path::new("todos")));
+}
+
+#[test]
+fn test_export_round_trip() {
+    let db = store();
+
+    for format in [Format::Json, Format::Jsonl, Format::Csv] {
+        let todos = exported(&db, format);
+        assert_eq!(2, todos.len());
+        assert_eq!("foo", todos[0].name);
+        assert_eq!(vec!["a", "b, \"c\""], todos[0].tags);
+        assert_eq!("2030-01-02", todos[0].due_date.unwrap().to_string());
+        assert_eq!(Status::Done, todos[1].status);
+        assert!(todos[1].created_at.is_some());
+    }
+}
+
+#[test]
+fn test_read_invalid_import() {
+    let csv = "name,status\nfoo,nope\n";
+    assert!(matches!(
+        read_todos(csv.as_bytes(), Format::Csv),
+        Err(Error::InvalidImport(_))
+    ));
+    assert!(matches!(
+        read_todos("[{".as_bytes(), Format::Json),
+        Err(Error::InvalidImport(_))
+    ));
+
+    let jsonl = "{\"name\":\"foo\"}\n{\"name\":\"bar\"}\n";
+    assert_eq!(2, read_todos(jsonl.as_bytes(), Format::Json).unwrap().len());
+}
+
+#[test]
+fn test_import_modes() {
+    let todos = exported(&store(), Format::Json);
+
+    let db = MemoryStore::new();
+    add_todo(&db, "bar", Some(&String::from("mine")), None, None, None).unwrap();
+    let report = import_todos(&db, &todos, ImportMode::SkipExisting).unwrap();
+    assert_eq!(
+        ImportReport {
+            added: names(&["foo"]),
+            updated: vec![],
+            skipped: names(&["bar"]),
+        },
+        report
+    );
+    assert_eq!("mine", get_todo(&db, "bar").unwrap().note);
+    assert_eq!(2, get_todo(&db, "foo").unwrap().id);
+
+    let report = import_todos(&db, &todos, ImportMode::Merge).unwrap();
+    assert_eq!(names(&["foo", "bar"]), report.updated);
+    let bar = get_todo(&db, "bar").unwrap();
+    assert_eq!((1, Status::Done), (bar.id, bar.status));
+
+    add_todo(&db, "baz", None, None, None, None).unwrap();
+    let report = import_todos(&db, &todos, ImportMode::Replace).unwrap();
+    assert_eq!(names(&["foo", "bar"]), report.added);
+    assert!(matches!(get_todo(&db, "baz"), Err(Error::NotFound)));
+    assert_eq!(1, get_todo(&db, "foo").unwrap().id);
+    assert_eq!(2, get_todo(&db, "bar").unwrap().id);
+    assert_eq!(4, add_todo(&db, "qux", None, None, None, None).unwrap().id);
+}
+
+#[test]
+fn test_import_requires_names() {
+    let db = MemoryStore::new();
+    let todos = vec![Todo::default()];
+
+    assert!(matches!(
+        import_todos(&db, &todos, ImportMode::Merge),
+        Err(Error::InvalidImport(_))
+    ));
+}
+
+#[test]
+fn test_export_jsonl() {
+    let mut out = Vec::new();
+    export_todos(&store(), &mut out, Format::Jsonl).unwrap();
+
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(2, out.lines().count());
+    assert!(out.lines().all(|line| line.starts_with("{\"")));
+}
+
+#[test]
+fn test_import_rejects_newer_versions() {
+    let db = MemoryStore::new();
+    let todos = vec![Todo {
+        name: String::from("foo"),
+        version: SCHEMA_VERSION + 1,
+        ..Todo::default()
+    }];
+
+    assert!(matches!(
+        import_todos(&db, &todos, ImportMode::Merge),
+        Err(Error::UnsupportedVersion(_))
+    ));
+    assert!(matches!(get_todo(&db, "foo"), Err(Error::NotFound)));
+}
+
+#[test]
+fn test_csv_keeps_names_and_notes_as_they_are() {
+    let db = MemoryStore::new();
+    let note = String::from("  indented\n");
+    add_todo(&db, " foo", Some(&note), None, None, None).unwrap();
+
+    let todos = exported(&db, Format::Csv);
+
+    assert_eq!(" foo", todos[0].name);
+    assert_eq!("  indented\n", todos[0].note);
+}